@@ -0,0 +1,184 @@
+use crate::request::{Catalog, Column, Model, Parameter, Query, Schema};
+
+use super::{
+    column_type, plural, row_output, select_where, table_namespace, CatalogQueries, CatalogQuery,
+    RowKey,
+};
+
+/// A single column foreign key between two models of the catalog.
+struct ForeignKey<'a> {
+    schema: &'a Schema,
+    model: &'a Model,
+    column: &'a Column,
+    parent_schema: &'a Schema,
+    parent: &'a Model,
+    parent_column: &'a Column,
+}
+
+impl CatalogQueries {
+    /// Includes `{column}_of` accessors on the referencing table namespace
+    /// and `{tables}_of` accessors on the referenced table namespace, along
+    /// with `_many` variants that load the relations of many records at once,
+    /// returned by the key of each record.
+    pub fn include_foreign_keys(&mut self) {
+        let catalog = self.request.catalog.clone();
+        let foreign_keys: Vec<_> = catalog
+            .schemas
+            .iter()
+            .flat_map(|schema| schema.models.iter().map(move |model| (schema, model)))
+            .flat_map(|(schema, model)| model.columns.iter().map(move |c| (schema, model, c)))
+            .filter_map(|(schema, model, column)| {
+                Self::foreign_key(&catalog, schema, model, column)
+            })
+            .collect();
+
+        for foreign_key in foreign_keys.iter() {
+            let is_ambiguous = foreign_keys
+                .iter()
+                .filter(|other| {
+                    std::ptr::eq(other.model, foreign_key.model)
+                        && std::ptr::eq(other.parent, foreign_key.parent)
+                })
+                .count()
+                > 1;
+
+            self.include_parent_accessors(foreign_key);
+            self.include_children_accessors(foreign_key, is_ambiguous);
+        }
+    }
+
+    fn foreign_key<'a>(
+        catalog: &'a Catalog,
+        schema: &'a Schema,
+        model: &'a Model,
+        column: &'a Column,
+    ) -> Option<ForeignKey<'a>> {
+        let (parent_schema, parent, parent_column) = catalog.parent(column)?;
+
+        Some(ForeignKey {
+            schema,
+            model,
+            column,
+            parent_schema,
+            parent,
+            parent_column,
+        })
+    }
+
+    fn include_parent_accessors(&mut self, fk: &ForeignKey) {
+        let stem = fk
+            .column
+            .name
+            .strip_suffix("_id")
+            .unwrap_or(&fk.column.name);
+        let namespace = table_namespace(fk.schema, &fk.model.name);
+        let select = select_where(fk.parent_schema, fk.parent, fk.parent_column);
+
+        self.queries.push(CatalogQuery {
+            option: "foreign_key_accessors",
+            query: Query {
+                query: format!("{select} = $1").into(),
+                name: format!("{stem}_of").into(),
+                command: "one".into(),
+                path: Default::default(),
+                annotations: Self::annotations(&namespace, !fk.column.is_nullable),
+                comment: None,
+                output: [row_output(fk.parent_schema, fk.parent)].into(),
+                parameters: [Parameter {
+                    name: format!("{}.{}", fk.model.name, fk.column.name).into(),
+                    type_: column_type(fk.column, false),
+                    not_null: !fk.column.is_nullable,
+                    is_list: false,
+                }]
+                .into(),
+            },
+            key: None,
+        });
+
+        self.queries.push(CatalogQuery {
+            option: "foreign_key_accessors",
+            query: Query {
+                query: format!("{select} = any($1)").into(),
+                name: format!("{stem}_of_many").into(),
+                command: "many".into(),
+                path: Default::default(),
+                annotations: Self::annotations(&namespace, false),
+                comment: None,
+                output: [row_output(fk.parent_schema, fk.parent)].into(),
+                parameters: [Parameter {
+                    name: format!("{stem}_{}", plural(&fk.parent_column.name)).into(),
+                    type_: column_type(fk.parent_column, true),
+                    not_null: true,
+                    is_list: false,
+                }]
+                .into(),
+            },
+            key: Some(RowKey {
+                field: fk.parent_column.name.clone(),
+                r#type: column_type(fk.parent_column, false),
+                grouped: false,
+            }),
+        });
+    }
+
+    fn include_children_accessors(&mut self, fk: &ForeignKey, is_ambiguous: bool) {
+        let name = if is_ambiguous {
+            let stem = fk
+                .column
+                .name
+                .strip_suffix("_id")
+                .unwrap_or(&fk.column.name);
+            format!("{}_of_{}", plural(&fk.model.name), stem)
+        } else {
+            format!("{}_of", plural(&fk.model.name))
+        };
+        let namespace = table_namespace(fk.parent_schema, &fk.parent.name);
+        let select = select_where(fk.schema, fk.model, fk.column);
+
+        self.queries.push(CatalogQuery {
+            option: "foreign_key_accessors",
+            query: Query {
+                query: format!("{select} = $1").into(),
+                name: name.clone().into(),
+                command: "many".into(),
+                path: Default::default(),
+                annotations: Self::annotations(&namespace, false),
+                comment: None,
+                output: [row_output(fk.schema, fk.model)].into(),
+                parameters: [Parameter {
+                    name: format!("{}.{}", fk.parent.name, fk.parent_column.name).into(),
+                    type_: column_type(fk.parent_column, false),
+                    not_null: true,
+                    is_list: false,
+                }]
+                .into(),
+            },
+            key: None,
+        });
+
+        self.queries.push(CatalogQuery {
+            option: "foreign_key_accessors",
+            query: Query {
+                query: format!("{select} = any($1)").into(),
+                name: format!("{name}_many").into(),
+                command: "many".into(),
+                path: Default::default(),
+                annotations: Self::annotations(&namespace, false),
+                comment: None,
+                output: [row_output(fk.schema, fk.model)].into(),
+                parameters: [Parameter {
+                    name: format!("{}_{}", fk.parent.name, plural(&fk.parent_column.name)).into(),
+                    type_: column_type(fk.parent_column, true),
+                    not_null: true,
+                    is_list: false,
+                }]
+                .into(),
+            },
+            key: Some(RowKey {
+                field: fk.column.name.clone(),
+                r#type: column_type(fk.parent_column, false),
+                grouped: true,
+            }),
+        });
+    }
+}
@@ -0,0 +1,129 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use crate::{
+    request::{Annotation, Column, Model, OutputColumn, OutputType, Query, Request, Schema},
+    utils::quote_identifier,
+};
+
+mod foreign_keys;
+mod unique_keys;
+
+/// It creates queries that are derived from the `Catalog` instead of
+/// being written by the user, such as relationship accessors.
+pub struct CatalogQueries {
+    request: Request,
+    queries: Vec<CatalogQuery>,
+}
+
+/// A query derived from the catalog.
+pub struct CatalogQuery {
+    pub query: Query,
+    /// The option that generates the query.
+    pub option: &'static str,
+    /// Set for lookups of many keys at once, whose rows are returned by key.
+    pub key: Option<RowKey>,
+}
+
+/// The field of the returned rows that a lookup of many keys is keyed by.
+pub struct RowKey {
+    pub field: Rc<str>,
+    pub r#type: OutputType,
+    /// Whether a key has a list of rows, rather than at most one row.
+    pub grouped: bool,
+}
+
+impl CatalogQueries {
+    pub fn from_request(request: &Request) -> Vec<CatalogQuery> {
+        let mut catalog_queries = CatalogQueries {
+            request: request.clone(),
+            queries: vec![],
+        };
+
+        let codegen = &request.config.codegen;
+
+        if codegen.option("foreign_key_accessors").unwrap_or(false) {
+            catalog_queries.include_foreign_keys();
+        }
+
+        if codegen.option("unique_lookups").unwrap_or(false) {
+            catalog_queries.include_unique_keys();
+        }
+
+        catalog_queries.queries
+    }
+
+    fn annotations(namespace: &str, not_null_result: bool) -> Rc<BTreeMap<String, Annotation>> {
+        let mut annotations = BTreeMap::new();
+        annotations.insert(
+            "namespace".into(),
+            Annotation {
+                value: Some(namespace.into()),
+                line: 0,
+            },
+        );
+        if not_null_result {
+            annotations.insert("not_null_result".into(), Annotation::default());
+        }
+        Rc::new(annotations)
+    }
+}
+
+/// The namespace generated methods for a table are placed in.
+fn table_namespace(schema: &Schema, table: &str) -> String {
+    if &*schema.name == "public" {
+        return table.into();
+    }
+    format!("{}.{}", schema.name, table)
+}
+
+/// Selects the rows of a model as a composite filtered by one of its columns.
+fn select_where(schema: &Schema, model: &Model, column: &Column) -> String {
+    format!(
+        "select {} from {}.{} where {}",
+        quote_identifier(&model.name),
+        quote_identifier(&schema.name),
+        quote_identifier(&model.name),
+        quote_identifier(&column.name),
+    )
+}
+
+/// The plural of a table or column name, which is kept as is when it already ends in `s`.
+fn plural(name: &str) -> String {
+    if name.ends_with('s') {
+        return name.into();
+    }
+    if let Some(stem) = name.strip_suffix('y') {
+        if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+            return format!("{stem}ies");
+        }
+    }
+    if name.ends_with(['x', 'z']) || name.ends_with("ch") || name.ends_with("sh") {
+        return format!("{name}es");
+    }
+    format!("{name}s")
+}
+
+fn row_output(schema: &Schema, model: &Model) -> OutputColumn {
+    OutputColumn {
+        name: model.name.clone(),
+        type_: OutputType {
+            schema: schema.name.clone(),
+            name: model.name.clone(),
+            id: 0,
+        },
+    }
+}
+
+fn column_type(column: &Column, array: bool) -> OutputType {
+    let name = if array {
+        format!("_{}", column.type_field.name).into()
+    } else {
+        column.type_field.name.clone()
+    };
+
+    OutputType {
+        schema: column.type_field.schema_name.clone(),
+        name,
+        id: 0,
+    }
+}
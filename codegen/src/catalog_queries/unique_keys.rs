@@ -0,0 +1,72 @@
+use crate::request::{Parameter, Query};
+
+use super::{
+    column_type, plural, row_output, select_where, table_namespace, CatalogQueries, CatalogQuery,
+};
+
+impl CatalogQueries {
+    /// Includes `get_by_{column}` and `get_many_by_{column}` lookups
+    /// for every unique or primary key column of a model. The columns of
+    /// a composite primary key are not unique on their own and are skipped.
+    pub fn include_unique_keys(&mut self) {
+        let catalog = self.request.catalog.clone();
+        for schema in catalog.schemas.iter() {
+            for model in schema.models.iter() {
+                let namespace = table_namespace(schema, &model.name);
+                let is_composite_key =
+                    model.columns.iter().filter(|c| c.is_primary_key).count() > 1;
+                let unique_columns = model
+                    .columns
+                    .iter()
+                    .filter(|c| c.is_unique || (c.is_primary_key && !is_composite_key))
+                    .filter(|column| column.type_field.array_dimensions == 0);
+
+                for column in unique_columns {
+                    let select = select_where(schema, model, column);
+
+                    self.queries.push(CatalogQuery {
+                        option: "unique_lookups",
+                        query: Query {
+                            query: format!("{select} = $1").into(),
+                            name: format!("get_by_{}", column.name).into(),
+                            command: "one".into(),
+                            path: Default::default(),
+                            annotations: Self::annotations(&namespace, false),
+                            comment: None,
+                            output: [row_output(schema, model)].into(),
+                            parameters: [Parameter {
+                                name: column.name.clone(),
+                                type_: column_type(column, false),
+                                not_null: true,
+                                is_list: false,
+                            }]
+                            .into(),
+                        },
+                        key: None,
+                    });
+
+                    self.queries.push(CatalogQuery {
+                        option: "unique_lookups",
+                        query: Query {
+                            query: format!("{select} = any($1)").into(),
+                            name: format!("get_many_by_{}", column.name).into(),
+                            command: "many".into(),
+                            path: Default::default(),
+                            annotations: Self::annotations(&namespace, false),
+                            comment: None,
+                            output: [row_output(schema, model)].into(),
+                            parameters: [Parameter {
+                                name: plural(&column.name).into(),
+                                type_: column_type(column, true),
+                                not_null: true,
+                                is_list: false,
+                            }]
+                            .into(),
+                        },
+                        key: None,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::rc::Rc;
 
 use thiserror::Error;
+
+use crate::response::Diagnostic;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("failed to deserialize request: {0}.\nThis may be a versioning issue between pgc and the codegen plugin being used.")]
@@ -9,6 +12,29 @@ pub enum Error {
     #[error("language {0} is not supported.")]
     NotSupportedLanguage(Rc<str>),
 
+    #[error("invalid codegen option \"{name}\": {reason}.")]
+    InvalidOption { name: Rc<str>, reason: String },
+
+    #[error("the single_file layout is not supported by {0}.")]
+    NotSupportedLayout(Rc<str>),
+
+    #[error("query \"{name}\" at \"{path}\" cannot be used with :copyfrom, expected a query of the form `insert into table (columns) values ($1, $2, ...)`.")]
+    InvalidCopyFrom { name: Rc<str>, path: Rc<str> },
+
+    #[error("query \"{name}\" at \"{path}\" cannot be used with :batch, expected a query with at least one parameter.")]
+    InvalidBatch { name: Rc<str>, path: Rc<str> },
+
+    #[error("invalid @{annotation} annotation at \"{path}:{line}\": {reason}.")]
+    InvalidAnnotation {
+        annotation: Rc<str>,
+        path: Rc<str>,
+        line: i64,
+        reason: String,
+    },
+
+    #[error("found name collisions in the generated code:{}", .0.iter().map(|diagnostic| format!("\n  {diagnostic}")).collect::<String>())]
+    NameCollisions(Vec<Diagnostic>),
+
     #[error("failed to render or parse a template: {0}.\nThis is a bug in pgc, please report the issue at \"https://github.com/tvallotton/pgc\".")]
     TemplateError(#[from] minijinja::Error),
 }
@@ -1,16 +1,57 @@
-use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, rc::Rc};
 
-use crate::{error::Error, request::Request};
+use indexmap::IndexSet;
+use minijinja::context;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{error::Error, request::Request, utils::render};
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct FileGenConfig {
     pub extension: String,
     pub directory_entrypoint: Option<String>,
+    /// Identifiers that cannot be used verbatim in the target language.
+    #[serde(default)]
+    pub reserved_words: BTreeSet<String>,
+    /// Renders a reserved word `name` as a valid identifier, such as `{{ name }}_`.
+    #[serde(default)]
+    pub escape: String,
+    /// How modules are joined with the `single_file` layout, if the target supports it.
+    #[serde(default)]
+    pub single_file: Option<SingleFileConfig>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SingleFileConfig {
+    /// The first line of an import statement, which is followed by its indented lines.
+    #[serde(with = "pattern")]
+    pub import: Regex,
+    /// Lines around the import statements that are left out, such as comments.
+    #[serde(with = "pattern")]
+    pub skip: Regex,
+}
+
+/// Compiles the patterns of a target config once, when it is loaded.
+mod pattern {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(regex: &Regex, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(regex.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map_err(serde::de::Error::custom)
+    }
 }
 
 impl FileGenConfig {
     pub fn new(request: &Request) -> Result<FileGenConfig, Error> {
-        let target = &request.config.codegen.target;
+        Self::for_target(&request.config.codegen.target)
+    }
+
+    pub fn for_target(target: &Rc<str>) -> Result<FileGenConfig, Error> {
         let json = match &**target {
             "python:asyncpg" => include_str!("../templates/python:asyncpg/config.json"),
             "python:psycopg" => include_str!("../templates/python:psycopg/config.json"),
@@ -18,4 +59,48 @@ impl FileGenConfig {
         };
         Ok(serde_json::from_str(json).unwrap())
     }
+
+    /// Escapes every reserved word of a dotted path such as `book.class`. Only
+    /// the generated identifiers are escaped, while names sent to or read from
+    /// the database driver keep their original spelling.
+    pub fn escape_identifier(&self, identifier: &str) -> String {
+        identifier
+            .split('.')
+            .map(|part| {
+                if self.reserved_words.contains(part) {
+                    render(&self.escape, context!(name => part))
+                } else {
+                    part.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+impl SingleFileConfig {
+    /// Moves the import statements at the top of a rendered module into `imports`,
+    /// and returns the rest of the module.
+    pub fn hoist(&self, content: &str, imports: &mut IndexSet<String>) -> String {
+        let mut lines = content.lines().peekable();
+        let mut statement: Option<String> = None;
+
+        while let Some(line) = lines.peek() {
+            if self.import.is_match(line) {
+                imports.extend(statement.replace(line.to_string()));
+            } else if let Some(statement) = statement
+                .as_mut()
+                .filter(|_| line.starts_with(char::is_whitespace) && !line.trim().is_empty())
+            {
+                statement.push('\n');
+                statement.push_str(line);
+            } else if !self.skip.is_match(line) {
+                break;
+            }
+            lines.next();
+        }
+        imports.extend(statement);
+
+        lines.collect::<Vec<_>>().join("\n").trim_end().to_string()
+    }
 }
@@ -1,18 +1,21 @@
+use indexmap::IndexSet;
 use minijinja::{context, Environment};
-use serde_json::json;
-use std::collections::BTreeSet;
+use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
 use crate::{
     error::Error,
     file_gen_config::FileGenConfig,
     jinja_environment_builder::{
-        JinjaEnvironmentBuilder, MODELS_DIR_ENTRYPOINT, MODEL_SCHEMA_FILE, QUERY,
+        JinjaEnvironmentBuilder, MODELS_DIR_ENTRYPOINT, MODEL_SCHEMA_FILE, QUERY, SINGLE_FILE,
     },
     model_modules::{ModelModule, ModelModules},
-    query_namespace::{QueryNamespace, QueryNamespaceBuilder},
+    name_collisions::NameCollisions,
+    naming::{Identifiers, Naming},
+    query_namespace::QueryNamespace,
     request::Request,
-    response::File,
+    response::{Diagnostic, File},
+    row_models::RowModels,
 };
 
 pub struct FileGenerator {
@@ -21,14 +24,55 @@ pub struct FileGenerator {
     pub model_modules: ModelModules,
     pub namespace: QueryNamespace,
     pub request: Request,
+    pub layout: Layout,
+}
+
+/// How the generated modules are laid out, set with the `layout` option.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    /// A module per schema in `models`, and a module per namespace.
+    #[default]
+    Package,
+    /// Every model and namespace in the directory entrypoint.
+    SingleFile,
+}
+
+/// How queries are reported, set with the `instrumentation` option.
+/// The templates read the option directly, it is only validated here.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Instrumentation {
+    /// Every query is reported to the function set with `set_query_hook`.
+    Hook,
+    /// Every query is traced in an OpenTelemetry span.
+    Opentelemetry,
 }
 
 impl FileGenerator {
     pub fn new(request: &Request) -> Result<FileGenerator, Error> {
-        let environment = JinjaEnvironmentBuilder::new(request).build()?;
+        let environment = JinjaEnvironmentBuilder::new(request)?.build()?;
         let config = FileGenConfig::new(request)?;
-        let model_modules = ModelModules::new(request)?;
-        let namespace = QueryNamespace::from_request(request)?;
+        request
+            .config
+            .codegen
+            .try_option::<Instrumentation>("instrumentation")?;
+        let layout = request
+            .config
+            .codegen
+            .try_option("layout")?
+            .unwrap_or_default();
+        if layout == Layout::SingleFile && config.single_file.is_none() {
+            return Err(Error::NotSupportedLayout(
+                request.config.codegen.target.clone(),
+            ));
+        }
+        let mut model_modules = ModelModules::new(request)?;
+        let mut namespace = QueryNamespace::from_request(request)?;
+        model_modules.rows =
+            RowModels::deduplicate(request, &mut namespace, model_modules.type_builder());
+        let identifiers = Identifiers::new(config.clone(), Naming::new(request)?);
+        NameCollisions::check(&namespace, &model_modules, &identifiers, layout)?;
 
         Ok(FileGenerator {
             environment,
@@ -36,10 +80,15 @@ impl FileGenerator {
             namespace,
             model_modules,
             request: request.clone(),
+            layout,
         })
     }
 
     pub fn render_files(&self) -> Result<Vec<File>, Error> {
+        if self.layout == Layout::SingleFile {
+            return Ok(vec![self.single_file()?]);
+        }
+
         let mut files = self.model_module_files()?;
         files.extend(self.model_dir_entrypoint()?);
         self.query_files(&mut files)?;
@@ -47,31 +96,94 @@ impl FileGenerator {
         Ok(files)
     }
 
+    /// Deprecated queries are still generated, but listed so they can be phased out.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.namespace.diagnostics()
+    }
+
+    /// Renders the modules of the package layout, and joins them in a single
+    /// module with their imports at the top. Model modules are nested in `models`,
+    /// and namespaces are declared before their parents.
+    fn single_file(&self) -> Result<File, Error> {
+        let single_file = self.config.single_file.as_ref().unwrap();
+        let mut imports = IndexSet::new();
+
+        let mut model_modules = vec![];
+        let rows = self.model_modules.rows.iter().map(|rows| ("rows", rows));
+        let modules = self.model_modules.model_modules.iter();
+        for (name, module) in modules.map(|(name, module)| (&**name, module)).chain(rows) {
+            let file = self.render_model_module(name, module)?;
+            model_modules.push((name, single_file.hoist(&file.content, &mut imports)));
+        }
+
+        let mut namespaces = vec![];
+        self.single_file_namespaces(&self.namespace, &mut imports, &mut namespaces)?;
+
+        let content = self
+            .environment
+            .get_template(SINGLE_FILE)?
+            .render(context! {
+                imports,
+                model_modules,
+                namespaces,
+                request => &self.request,
+            })?;
+        Ok(File {
+            path: format!(
+                "./{}.{}",
+                self.directory_entrypoint(),
+                self.config.extension
+            ),
+            content,
+        })
+    }
+
+    fn single_file_namespaces(
+        &self,
+        namespace: &QueryNamespace,
+        imports: &mut IndexSet<String>,
+        namespaces: &mut Vec<String>,
+    ) -> Result<(), Error> {
+        for subnamespace in namespace.subnamespaces.values() {
+            self.single_file_namespaces(subnamespace, imports, namespaces)?;
+        }
+        let content = self.render_namespace(namespace)?;
+        let single_file = self.config.single_file.as_ref().unwrap();
+        namespaces.push(single_file.hoist(&content, imports));
+        Ok(())
+    }
+
     fn model_module_files(&self) -> Result<Vec<File>, Error> {
         let mut files = vec![];
         for (name, module) in self.model_modules.model_modules.iter() {
-            let filename = format!("models/{}.{}", name, &self.config.extension);
-
-            let content = self
-                .environment
-                .get_template(MODEL_SCHEMA_FILE)?
-                .render(context! {
-                    imports => module.imports(),
-                    schema => name,
-                    models => &module.classes,
-                    enums => &module.enums,
-                    request => &self.request,
-                })?;
-
-            let file = File {
-                path: filename,
-                content,
-            };
-            files.push(file);
+            files.push(self.render_model_module(name, module)?);
+        }
+        if let Some(rows) = &self.model_modules.rows {
+            files.push(self.render_model_module("rows", rows)?);
         }
         Ok(files)
     }
 
+    fn render_model_module(&self, name: &str, module: &ModelModule) -> Result<File, Error> {
+        let filename = format!("models/{}.{}", name, &self.config.extension);
+
+        let content = self
+            .environment
+            .get_template(MODEL_SCHEMA_FILE)?
+            .render(context! {
+                imports => module.imports(),
+                schema => name,
+                models => &module.classes,
+                enums => &module.enums,
+                request => &self.request,
+            })?;
+
+        Ok(File {
+            path: filename,
+            content,
+        })
+    }
+
     fn model_dir_entrypoint(&self) -> Result<Option<File>, Error> {
         let Some(filename) = self.config.directory_entrypoint.clone() else {
             return Ok(None);
@@ -82,6 +194,7 @@ impl FileGenerator {
             .get_template(MODELS_DIR_ENTRYPOINT)?
             .render(context!(
                 model_modules=> &self.model_modules.model_modules,
+                rows => &self.model_modules.rows,
                 request => &self.request,
             ))?;
         let path = format!("models/{filename}.{}", self.config.extension);
@@ -107,7 +220,7 @@ impl FileGenerator {
             } else {
                 &namespace.name
             };
-            let path = dir_path.join(&name);
+            let path = dir_path.join(name);
             let file = self.render_query_file(&path, namespace)?;
             files.push(file);
         } else {
@@ -124,23 +237,26 @@ impl FileGenerator {
     }
 
     fn render_query_file(&self, path: &Path, namespace: &QueryNamespace) -> Result<File, Error> {
-        let content = self.environment.get_template(QUERY)?.render(&context! (
+        Ok(File {
+            path: format!("{}.{}", path.to_str().unwrap(), self.config.extension),
+            content: self.render_namespace(namespace)?,
+        })
+    }
+
+    fn render_namespace(&self, namespace: &QueryNamespace) -> Result<String, Error> {
+        Ok(self.environment.get_template(QUERY)?.render(&context! (
             query_namespace => namespace,
             imports => namespace.imports(),
             request => &self.request,
             model_modules => self.model_modules.model_modules,
-        ))?;
-
-        Ok(File {
-            path: format!("{}.{}", path.to_str().unwrap(), self.config.extension),
-            content,
-        })
+            paginated_tree => namespace.paginated_tree(),
+        ))?)
     }
 
     fn directory_entrypoint(&self) -> String {
         if let Some(entrypoint) = self.config.directory_entrypoint.as_ref() {
             return entrypoint.clone();
         }
-        return format!("query.{}", self.config.extension);
+        format!("query.{}", self.config.extension)
     }
 }
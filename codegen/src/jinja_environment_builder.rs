@@ -2,20 +2,29 @@ use std::rc::Rc;
 
 use minijinja::Environment;
 
-use crate::{error::Error, request::Request, utils};
+use crate::{
+    error::Error,
+    file_gen_config::FileGenConfig,
+    naming::{Identifiers, Naming},
+    request::Request,
+    utils,
+};
 
 pub struct JinjaEnvironmentBuilder {
     pub target: Rc<str>,
+    pub naming: Naming,
 }
-pub const QUERY: &'static str = "query";
-pub const MODEL_SCHEMA_FILE: &'static str = "model_schema_file";
-pub const MODELS_DIR_ENTRYPOINT: &'static str = "model_dir_entrypoint";
+pub const QUERY: &str = "query";
+pub const MODEL_SCHEMA_FILE: &str = "model_schema_file";
+pub const MODELS_DIR_ENTRYPOINT: &str = "model_dir_entrypoint";
+pub const SINGLE_FILE: &str = "single_file";
 
 impl JinjaEnvironmentBuilder {
-    pub fn new(request: &Request) -> Self {
-        Self {
+    pub fn new(request: &Request) -> Result<Self, Error> {
+        Ok(Self {
             target: request.config.codegen.target.clone(),
-        }
+            naming: Naming::new(request)?,
+        })
     }
 
     fn query_template(&self) -> Result<&'static str, Error> {
@@ -42,11 +51,42 @@ impl JinjaEnvironmentBuilder {
         })
     }
 
+    fn single_file_template(&self) -> Result<&'static str, Error> {
+        Ok(match &*self.target {
+            "python:asyncpg" => include_str!("../templates/python:asyncpg/single_file.py.jinja2"),
+            "python:psycopg" => include_str!("../templates/python:psycopg/single_file.py.jinja2"),
+            _ => return Err(Error::NotSupportedLanguage(self.target.clone())),
+        })
+    }
+
     pub fn build(self) -> Result<Environment<'static>, Error> {
         let mut environment = utils::env();
+        let identifiers = Identifiers::new(
+            FileGenConfig::for_target(&self.target)?,
+            self.naming.clone(),
+        );
+        let (escape, field, method, parameter) = (
+            identifiers.clone(),
+            identifiers.clone(),
+            identifiers.clone(),
+            identifiers,
+        );
+        environment.add_filter("escape_identifier", move |identifier: &str| {
+            escape.escape(identifier)
+        });
+        environment.add_filter("field_identifier", move |identifier: &str| {
+            field.field(identifier)
+        });
+        environment.add_filter("method_identifier", move |identifier: &str| {
+            method.method(identifier)
+        });
+        environment.add_filter("parameter_identifier", move |identifier: &str| {
+            parameter.parameter(identifier)
+        });
         environment.add_template(QUERY, self.query_template()?)?;
         environment.add_template(MODEL_SCHEMA_FILE, self.model_template()?)?;
         environment.add_template(MODELS_DIR_ENTRYPOINT, self.model_dir_entrypoint_template()?)?;
+        environment.add_template(SINGLE_FILE, self.single_file_template()?)?;
         Ok(environment)
     }
 }
@@ -1,3 +1,4 @@
+pub mod catalog_queries;
 pub mod error;
 pub mod file_gen_config;
 pub mod file_generator;
@@ -5,9 +6,12 @@ pub mod jinja_environment_builder;
 pub mod method;
 pub mod mock;
 pub mod model_modules;
+pub mod name_collisions;
+pub mod naming;
 pub mod query_namespace;
 pub mod request;
 pub mod response;
+pub mod row_models;
 pub mod template_context;
 pub mod r#type;
 pub mod type_builder;
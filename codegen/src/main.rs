@@ -1,12 +1,13 @@
 use crate::file_generator::FileGenerator;
 use crate::request::Request;
-use crate::response::{File, Response};
+use crate::response::Response;
 use error::Error;
 use serde::Serialize;
 use serde_json::json;
 use std::sync::atomic::Ordering::Relaxed;
 use std::{slice, sync::atomic::AtomicU64};
 
+pub mod catalog_queries;
 pub mod error;
 pub mod file_gen_config;
 pub mod file_generator;
@@ -14,9 +15,12 @@ pub mod jinja_environment_builder;
 pub mod method;
 pub mod mock;
 pub mod model_modules;
+pub mod name_collisions;
+pub mod naming;
 pub mod query_namespace;
 pub mod request;
 pub mod response;
+pub mod row_models;
 pub mod template_context;
 pub mod r#type;
 pub mod type_builder;
@@ -44,6 +48,7 @@ fn try_build(ptr: *mut u8, size: usize) -> Result<impl Serialize, Error> {
     let generator = FileGenerator::new(&request)?;
     Ok(Response {
         files: generator.render_files()?,
+        diagnostics: generator.diagnostics(),
     })
 }
 
@@ -65,7 +70,7 @@ fn load_request(ptr: *mut u8, size: usize) -> Result<Request, error::Error> {
     let buffer = unsafe { slice::from_raw_parts(ptr, size) };
     let payload = String::from_utf8(buffer.into()).unwrap();
     let request: Request = serde_json::from_str(&payload)?;
-    return Ok(request);
+    Ok(request)
 }
 
 fn main() {}
@@ -0,0 +1,93 @@
+use std::{rc::Rc, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, request::Query, utils::quote_identifier};
+
+static INSERT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?is)^\s*insert\s+into\s+([\w."]+)\s*\(([^)]*)\)\s*values\s*\(([^)]*)\)\s*;?\s*$"#,
+    )
+    .unwrap()
+});
+
+static IDENTIFIER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]+)"|(\w+)"#).unwrap());
+
+static PARAMETER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\$(\d+)$").unwrap());
+
+/// The target of a `:copyfrom` query, taken from an
+/// `insert into table (columns) values (parameters)` statement.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CopyFrom {
+    schema: Option<Rc<str>>,
+    table: Rc<str>,
+    columns: Vec<Rc<str>>,
+    /// The parameter that is copied into each column.
+    parameters: Vec<Rc<str>>,
+    /// A `copy ... from stdin` statement for drivers that take one.
+    statement: Rc<str>,
+}
+
+impl CopyFrom {
+    pub fn from_query(query: &Query) -> Result<CopyFrom, Error> {
+        let invalid = || Error::InvalidCopyFrom {
+            name: query.name.clone(),
+            path: query.path.clone(),
+        };
+
+        let captures = INSERT.captures(&query.query).ok_or_else(invalid)?;
+
+        let mut table: Vec<_> = identifiers(&captures[1]).collect();
+        let columns: Vec<_> = captures[2].split(',').flat_map(identifiers).collect();
+        let values: Vec<_> = captures[3].split(',').map(str::trim).collect();
+
+        if table.is_empty() || table.len() > 2 || columns.len() != values.len() {
+            return Err(invalid());
+        }
+
+        let parameters = values
+            .iter()
+            .map(|value| {
+                let index: usize = PARAMETER.captures(value)?[1].parse().ok()?;
+                Some(query.parameters.get(index.checked_sub(1)?)?.name.clone())
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+
+        let table_name = table.pop().unwrap();
+        let schema = table.pop();
+
+        let statement = format!(
+            "copy {}{} ({}) from stdin",
+            schema
+                .as_ref()
+                .map(|s| format!("{}.", quote_identifier(s)))
+                .unwrap_or_default(),
+            quote_identifier(&table_name),
+            columns
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+
+        Ok(CopyFrom {
+            schema,
+            table: table_name,
+            columns,
+            parameters,
+            statement: statement.into(),
+        })
+    }
+}
+
+/// Quoted identifiers are kept as is, while unquoted ones are folded to lower case.
+fn identifiers(text: &str) -> impl Iterator<Item = Rc<str>> + '_ {
+    IDENTIFIER.captures_iter(text).map(|captures| {
+        if let Some(quoted) = captures.get(1) {
+            return quoted.as_str().into();
+        }
+        captures[2].to_lowercase().into()
+    })
+}
@@ -3,7 +3,13 @@ use std::{collections::BTreeMap, mem::take, rc::Rc};
 use indexmap::IndexMap;
 
 use crate::{
-    method::{Method, MethodModel},
+    catalog_queries::CatalogQuery,
+    error::Error,
+    method::{
+        BatchArgument, CopyFrom, Deprecated, KeyedRows, Method, MethodModel, OptionalFilters,
+        Pagination, Sort, Transaction,
+    },
+    mock::Fake,
     r#type::Type,
     request::Query,
     type_builder::TypeBuilder,
@@ -24,43 +30,129 @@ impl MethodBuilder {
         }
     }
 
-    pub fn build(&mut self, query: &Query) -> Method {
-        self.init_input_models(query);
-        Method {
-            query: query.clone(),
+    pub fn build(&mut self, query: &Query) -> Result<Method, Error> {
+        let query = &OptionalFilters::apply(query)?;
+        self.init_input_models(query)?;
+        Self::check_returns(query)?;
+        let pagination = Pagination::from_query(query, self.type_builder.catalog())?;
+        let sort = Sort::from_query(query, self.type_builder.catalog())?;
+        let mut method_query = query.clone();
+        if let Some(pagination) = &pagination {
+            method_query.query = pagination.query();
+        }
+        let batch_argument = self.batch_argument(query)?;
+        let fake = self.fake(
+            query,
+            batch_argument.as_ref(),
+            pagination.is_some(),
+            sort.is_some(),
+        );
+
+        Ok(Method {
+            query: method_query,
+            batch_argument,
+            fake,
+            copy_from: self.copy_from(query)?,
+            prefetch: self.prefetch(query)?,
+            transaction: self.transaction(query)?,
+            doc: Self::doc(query),
+            deprecated: Self::deprecated(query),
+            page_model: pagination.as_ref().and_then(|_| self.page_model(query)),
+            pagination,
+            sort_by: sort.as_ref().map(|_| {
+                self.type_builder
+                    .declared(&format!("{}_sort_by", query.name))
+            }),
+            sort,
             arguments: take(&mut self.arguments),
             input_models: take(&mut self.input_models),
             output_type: self.output_type(query),
             output_model: self.output_model(query),
-        }
+            keyed_rows: None,
+            generated_by: None,
+        })
     }
 
-    pub fn init_input_models(&mut self, query: &Query) {
+    /// Builds a query derived from the catalog. The rows of a lookup of many
+    /// keys are returned in a mapping by their key.
+    pub fn build_catalog_query(&mut self, catalog_query: &CatalogQuery) -> Result<Method, Error> {
+        let mut method = self.build(&catalog_query.query)?;
+        method.generated_by = Some(catalog_query.option.into());
+        let (Some(key), Some(row)) = (&catalog_query.key, &method.output_type) else {
+            return Ok(method);
+        };
+
+        let key_type = self.type_builder.from_output_type(&key.r#type);
+        let rows = if key.grouped {
+            self.type_builder.array(row.clone(), 1)
+        } else {
+            row.clone()
+        };
+        method.keyed_rows = Some(KeyedRows {
+            field: key.field.clone(),
+            grouped: key.grouped,
+            r#type: self.type_builder.mapping(key_type, rows),
+        });
+        method.fake = method.fake.keyed();
+        Ok(method)
+    }
+
+    pub fn init_input_models(&mut self, query: &Query) -> Result<(), Error> {
+        let names = Self::input_names(query)?;
         for param in query.parameters.iter() {
             let mut ty = self.type_builder.from_output_type(&param.type_);
 
+            // An array described by its own type, as in `tags && $tags[]`, is already a list.
+            if param.is_list && !param.type_.name.starts_with('_') {
+                ty = self.type_builder.array(ty, 1);
+            }
+
             if !param.not_null {
                 ty = self.type_builder.null(&ty);
             }
 
             if let Some((record, field)) = param.name.split_once('.') {
-                self.include_input_model(record, field, ty, query);
+                let name = names.get(record).copied();
+                self.include_input_model(record, field, ty, query, name);
 
                 continue;
             };
 
             self.arguments.insert(param.name.clone(), ty);
         }
+
+        match names
+            .keys()
+            .find(|record| !self.input_models.contains_key(**record))
+        {
+            Some(record) => Err(Self::invalid_input(
+                query,
+                format!("the query has no parameters of the record \"{record}\""),
+            )),
+            None => Ok(()),
+        }
     }
 
-    pub fn include_input_model(&mut self, record: &str, field: &str, ty: Type, query: &Query) {
+    pub fn include_input_model(
+        &mut self,
+        record: &str,
+        field: &str,
+        ty: Type,
+        query: &Query,
+        name: Option<&str>,
+    ) {
         let query_name = query.name.clone();
         let type_builder = self.type_builder.clone();
         let entry = self.input_models.entry(record.into());
 
         let query_model = entry.or_insert_with(|| MethodModel {
-            r#type: type_builder.declared(&format!("{}_{}", query_name, record)),
+            r#type: match name {
+                Some(name) => type_builder.declared(name),
+                None => type_builder.declared(&format!("{}_{}", query_name, record)),
+            },
             fields: IndexMap::default(),
+            named: name.is_some(),
+            shared: false,
         });
 
         query_model.fields.insert(field.into(), ty);
@@ -69,26 +161,275 @@ impl MethodBuilder {
             .insert(record.into(), query_model.r#type.clone());
     }
 
+    /// The models named with `@input`, as in `@input: book: BookInput, author: AuthorInput`.
+    fn input_names(query: &Query) -> Result<BTreeMap<&str, &str>, Error> {
+        let Some(value) = query
+            .annotations
+            .get("input")
+            .map(|annotation| annotation.value.as_deref().unwrap_or_default())
+        else {
+            return Ok(BTreeMap::new());
+        };
+
+        let mut names = BTreeMap::new();
+        for entry in value.split(',') {
+            let name = entry
+                .split_once(':')
+                .map(|(record, name)| (record.trim(), name.trim()))
+                .filter(|(record, name)| !record.is_empty() && !name.is_empty());
+            let Some((record, name)) = name else {
+                return Err(Self::invalid_input(
+                    query,
+                    format!("expected \"record: Name\", found \"{}\"", entry.trim()),
+                ));
+            };
+            names.insert(record, name);
+        }
+        Ok(names)
+    }
+
+    fn invalid_input(query: &Query, reason: String) -> Error {
+        Error::InvalidAnnotation {
+            annotation: "input".into(),
+            path: query.path.clone(),
+            line: query.annotations.get("input").map_or(0, |input| input.line),
+            reason,
+        }
+    }
+
+    /// The name of the output model, set with `@returns`.
+    fn returns(query: &Query) -> Option<&str> {
+        let annotation = query.annotations.get("returns")?;
+        Some(annotation.value.as_deref().unwrap_or_default().trim())
+    }
+
+    fn check_returns(query: &Query) -> Result<(), Error> {
+        let Some(name) = Self::returns(query) else {
+            return Ok(());
+        };
+        let line = query
+            .annotations
+            .get("returns")
+            .map_or(0, |returns| returns.line);
+        let reason = if name.is_empty() {
+            "expected the name of the output model".to_string()
+        } else if query.output.len() < 2
+            || matches!(&*query.command, "exec" | "execrows" | "copyfrom")
+        {
+            "only queries returning more than one column have an output model".to_string()
+        } else {
+            return Ok(());
+        };
+        Err(Error::InvalidAnnotation {
+            annotation: "returns".into(),
+            path: query.path.clone(),
+            line,
+            reason,
+        })
+    }
+
+    /// A `:batch` or `:copyfrom` method takes a sequence of its arguments instead,
+    /// either of values when it has a single argument, or of tuples otherwise.
+    /// The sequence is named `params` for a `:batch` and `rows` for a `:copyfrom`.
+    fn batch_argument(&self, query: &Query) -> Result<Option<BatchArgument>, Error> {
+        let elements: Vec<_> = self.arguments.keys().cloned().collect();
+        let name: Rc<str> = match &*query.command {
+            "copyfrom" => "rows".into(),
+            _ => "params".into(),
+        };
+
+        match (&*query.command, self.arguments.first()) {
+            ("batch" | "copyfrom", Some((_, ty))) if self.arguments.len() == 1 => {
+                Ok(Some(BatchArgument {
+                    name,
+                    r#type: self.batch_type(query, ty.clone()),
+                    elements,
+                }))
+            }
+            ("batch" | "copyfrom", Some(_)) => {
+                let types: Vec<_> = self.arguments.values().cloned().collect();
+                Ok(Some(BatchArgument {
+                    name,
+                    r#type: self.batch_type(query, self.type_builder.tuple(&types)),
+                    elements,
+                }))
+            }
+            ("batch", None) => Err(Error::InvalidBatch {
+                name: query.name.clone(),
+                path: query.path.clone(),
+            }),
+            ("copyfrom", None) => Err(Error::InvalidCopyFrom {
+                name: query.name.clone(),
+                path: query.path.clone(),
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Rows of a `:copyfrom` are streamed, so any iterable is accepted.
+    fn batch_type(&self, query: &Query, element: Type) -> Type {
+        if &*query.command == "copyfrom" {
+            return self.type_builder.iterable(element);
+        }
+        self.type_builder.array(element, 1)
+    }
+
+    fn copy_from(&self, query: &Query) -> Result<Option<CopyFrom>, Error> {
+        if &*query.command != "copyfrom" {
+            return Ok(None);
+        }
+        CopyFrom::from_query(query).map(Some)
+    }
+
+    /// The number of rows an `:iter` method fetches at a time, set with `@prefetch`.
+    fn prefetch(&self, query: &Query) -> Result<Option<u64>, Error> {
+        let Some(annotation) = query.annotations.get("prefetch") else {
+            return Ok(None);
+        };
+
+        let value = annotation.value.as_deref().unwrap_or_default();
+        match value.trim().parse() {
+            Ok(prefetch) if prefetch > 0 => Ok(Some(prefetch)),
+            _ => Err(Error::InvalidAnnotation {
+                annotation: "prefetch".into(),
+                path: query.path.clone(),
+                line: annotation.line,
+                reason: format!("expected a positive integer, found \"{value}\""),
+            }),
+        }
+    }
+
+    fn fake(
+        &self,
+        query: &Query,
+        batch_argument: Option<&BatchArgument>,
+        paginated: bool,
+        sorted: bool,
+    ) -> Fake {
+        let mut arguments: Vec<Rc<str>> = match batch_argument {
+            Some(batch_argument) => vec![batch_argument.name.clone()],
+            None => self.arguments.keys().cloned().collect(),
+        };
+        if paginated {
+            arguments.extend(["page_size".into(), "cursor".into()]);
+        }
+        if sorted {
+            arguments.extend(["sort_by".into(), "descending".into()]);
+        }
+        Fake::new(query, arguments, paginated)
+    }
+
+    /// The `@doc` annotation followed by the comment block above the query.
+    fn doc(query: &Query) -> Option<Rc<str>> {
+        let annotation = query
+            .annotations
+            .get("doc")
+            .and_then(|doc| doc.value.as_deref());
+        let paragraphs: Vec<&str> = annotation
+            .into_iter()
+            .chain(query.comment.as_deref())
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect();
+
+        (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n").into())
+    }
+
+    fn deprecated(query: &Query) -> Option<Deprecated> {
+        let annotation = query.annotations.get("deprecated")?;
+        let reason = annotation.value.as_deref().unwrap_or_default().trim();
+        let reason: Option<Rc<str>> = (!reason.is_empty()).then(|| reason.into());
+        Some(Deprecated {
+            message: reason
+                .clone()
+                .unwrap_or_else(|| format!("{} is deprecated", query.name).into()),
+            reason,
+            line: annotation.line,
+        })
+    }
+
+    fn transaction(&self, query: &Query) -> Result<Option<Transaction>, Error> {
+        let Some(annotation) = query.annotations.get("transaction") else {
+            return Ok(None);
+        };
+
+        let value = annotation.value.as_deref().unwrap_or_default().trim();
+        let isolation = value
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("_");
+
+        match &*isolation {
+            "" => Ok(Some(Transaction { isolation: None })),
+            "serializable" | "repeatable_read" | "read_committed" => Ok(Some(Transaction {
+                isolation: Some(isolation.into()),
+            })),
+            _ => Err(Error::InvalidAnnotation {
+                annotation: "transaction".into(),
+                path: query.path.clone(),
+                line: annotation.line,
+                reason: format!(
+                    "expected serializable, repeatable read or read committed, found \"{value}\""
+                ),
+            }),
+        }
+    }
+
     fn output_type(&self, query: &Query) -> Option<Type> {
         if &*query.command == "exec" {
             return None;
         }
 
-        if query.output.len() == 0 {
+        if matches!(&*query.command, "execrows" | "copyfrom") {
+            return Some(
+                self.type_builder
+                    .resolve(&"pg_catalog".into(), &"int8".into()),
+            );
+        }
+
+        if query.output.is_empty() {
             return None;
         }
 
         if query.output.len() == 1 {
             let pg_type = &query.output[0].type_;
-            let output_type = self.type_builder.from_output_type(&pg_type);
+            let output_type = self.type_builder.from_output_type(pg_type);
             return Some(output_type);
         }
 
-        Some(self.type_builder.declared(&format!("{}_row", query.name)))
+        if let Some(name) = Self::returns(query) {
+            return Some(self.type_builder.declared(name));
+        }
+
+        let suffix = self.type_builder.naming().row_suffix();
+        Some(
+            self.type_builder
+                .declared(&format!("{}_{}", query.name, suffix)),
+        )
+    }
+
+    /// The page returned by a paginated method, with its rows and the cursor of the next page.
+    fn page_model(&self, query: &Query) -> Option<MethodModel> {
+        let item = self.output_type(query)?;
+        let text = self
+            .type_builder
+            .resolve(&"pg_catalog".into(), &"text".into());
+
+        let mut fields = IndexMap::new();
+        fields.insert("items".into(), self.type_builder.array(item, 1));
+        fields.insert("next_cursor".into(), self.type_builder.null(&text));
+
+        Some(MethodModel {
+            r#type: self.type_builder.declared(&format!("{}_page", query.name)),
+            fields,
+            named: false,
+            shared: false,
+        })
     }
 
     fn output_model(&self, query: &Query) -> Option<MethodModel> {
-        if query.output.len() < 2 {
+        if query.output.len() < 2 || matches!(&*query.command, "exec" | "execrows" | "copyfrom") {
             return None;
         }
         let columns = query
@@ -103,6 +444,8 @@ impl MethodBuilder {
         Some(MethodModel {
             r#type: self.output_type(query)?,
             fields: columns,
+            named: Self::returns(query).is_some(),
+            shared: false,
         })
     }
 }
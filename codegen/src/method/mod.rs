@@ -1,15 +1,20 @@
-use std::{
-    collections::{btree_map::Entry, BTreeMap},
-    rc::Rc,
-};
+use std::{collections::BTreeMap, rc::Rc};
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::{r#type::Type, request::Query};
+use crate::{mock::Fake, r#type::Type, request::Query, response::Diagnostic};
 
+pub use copy_from::CopyFrom;
 pub use method_builder::MethodBuilder;
+pub use optional_filters::OptionalFilters;
+pub use pagination::Pagination;
+pub use sort::Sort;
+mod copy_from;
 mod method_builder;
+mod optional_filters;
+mod pagination;
+mod sort;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Method {
@@ -18,20 +23,205 @@ pub struct Method {
     input_models: BTreeMap<Rc<str>, MethodModel>,
     pub output_type: Option<Type>,
     output_model: Option<MethodModel>,
+    keyed_rows: Option<KeyedRows>,
+    /// The option that derives the method from the catalog, if it is not a hand-written query.
+    generated_by: Option<Rc<str>>,
+    batch_argument: Option<BatchArgument>,
+    copy_from: Option<CopyFrom>,
+    prefetch: Option<u64>,
+    pagination: Option<Pagination>,
+    page_model: Option<MethodModel>,
+    sort: Option<Sort>,
+    transaction: Option<Transaction>,
+    /// The documentation of the method, from `@doc` and the comment above the query.
+    doc: Option<Rc<str>>,
+    deprecated: Option<Deprecated>,
+    fake: Fake,
+    /// The enum of the columns a sorted method can be ordered by.
+    sort_by: Option<Type>,
+}
+
+/// The argument of a `:batch` or `:copyfrom` method, a sequence with the
+/// arguments of every execution or row.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BatchArgument {
+    name: Rc<str>,
+    r#type: Type,
+    elements: Vec<Rc<str>>,
+}
+
+/// The rows of a lookup of many keys, returned in a mapping by the key
+/// they were looked up by, such as the `_many` foreign key accessors.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct KeyedRows {
+    /// The field of the rows that holds their key.
+    field: Rc<str>,
+    /// Whether each key has a list of rows, rather than at most one row.
+    grouped: bool,
+    /// The mapping returned by the method.
+    r#type: Type,
+}
+
+/// Required by `@transaction`, optionally with the isolation level the
+/// transaction must have, as in `@transaction: serializable`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Transaction {
+    isolation: Option<Rc<str>>,
+}
+
+/// Set with `@deprecated`, optionally with the reason, as in
+/// `@deprecated: use fetch_all instead`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Deprecated {
+    /// The message of the warning, shown when the method is used.
+    message: Rc<str>,
+    reason: Option<Rc<str>>,
+    line: i64,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct MethodModel {
     r#type: Type,
     fields: IndexMap<Rc<str>, Type>,
+    /// Whether the model is named with `@returns` or `@input`, so that
+    /// queries with the same fields can share it.
+    #[serde(default)]
+    named: bool,
+    /// Whether the model is already declared, by an earlier query of the
+    /// namespace or in the `models.rows` module.
+    #[serde(default)]
+    shared: bool,
+}
+
+impl MethodModel {
+    pub fn r#type(&self) -> &Type {
+        &self.r#type
+    }
+
+    pub fn fields(&self) -> &IndexMap<Rc<str>, Type> {
+        &self.fields
+    }
+
+    pub fn named(&self) -> bool {
+        self.named
+    }
+
+    /// Whether both models have the same fields, in any order.
+    fn same_fields(&self, other: &MethodModel) -> bool {
+        self.fields.len() == other.fields.len()
+            && self.fields.iter().all(|(name, ty)| {
+                other
+                    .fields
+                    .get(name)
+                    .is_some_and(|other| other.annotation == ty.annotation)
+            })
+    }
 }
 
 impl Method {
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Deprecated methods are still generated, but listed so they can be phased out.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        let deprecated = self.deprecated.as_ref()?;
+        let mut message = format!("query \"{}\" is deprecated", self.query.name);
+        if let Some(reason) = &deprecated.reason {
+            message = format!("{message}: {reason}");
+        }
+        Some(Diagnostic {
+            path: self.query.path.clone(),
+            line: deprecated.line,
+            message,
+        })
+    }
+
+    pub fn generated_by(&self) -> Option<&str> {
+        self.generated_by.as_deref()
+    }
+
+    pub fn paginated(&self) -> bool {
+        self.pagination.is_some()
+    }
+
+    pub fn output_model(&self) -> Option<&MethodModel> {
+        self.output_model.as_ref()
+    }
+
+    /// Returns rows of the shared model `row`, declared outside of the namespace module.
+    /// The items of a page are a list of `items`.
+    pub fn use_row_model(&mut self, row: Type, items: Type) {
+        if let Some(page_model) = &mut self.page_model {
+            page_model.fields.insert("items".into(), items);
+        }
+        if let Some(output_model) = &mut self.output_model {
+            output_model.r#type = row.clone();
+            output_model.shared = true;
+        }
+        self.output_type = Some(row);
+    }
+
+    /// The types declared in the namespace module for this method.
+    pub fn declared_types(&self) -> impl Iterator<Item = &Type> + '_ {
+        self.models()
+            .filter(|model| !model.shared)
+            .map(|model| &model.r#type)
+            .chain(self.sort_by.iter())
+    }
+
+    /// Whether the method names a model `declaration` with `@returns` or `@input`.
+    pub fn names_model(&self, declaration: &str) -> bool {
+        self.models()
+            .any(|model| model.named && &*model.r#type.declaration == declaration)
+    }
+
+    /// Marks the named models of this method that `other` already declares
+    /// with the same fields, so they are only declared once.
+    pub fn share_models(&mut self, other: &Method) {
+        let models = self
+            .output_model
+            .iter_mut()
+            .chain(self.input_models.values_mut());
+        for model in models.filter(|model| model.named) {
+            let declared = other.models().any(|other| {
+                other.named
+                    && !other.shared
+                    && other.r#type.declaration == model.r#type.declaration
+                    && other.same_fields(model)
+            });
+            if declared {
+                model.shared = true;
+            }
+        }
+    }
+
+    fn models(&self) -> impl Iterator<Item = &MethodModel> + '_ {
+        self.output_model
+            .iter()
+            .chain(self.page_model.iter())
+            .chain(self.input_models.values())
+    }
+
     pub fn imports(&self) -> impl Iterator<Item = &str> + '_ {
-        let argument_imports = self.arguments.values().flat_map(|ty| ty.import.iter());
+        let argument_imports = self
+            .arguments
+            .values()
+            .chain(
+                self.batch_argument
+                    .as_ref()
+                    .map(|argument| &argument.r#type),
+            )
+            .chain(
+                self.keyed_rows
+                    .as_ref()
+                    .map(|keyed_rows| &keyed_rows.r#type),
+            )
+            .flat_map(|ty| ty.import.iter());
         self.input_models
             .values()
             .chain(self.output_model.as_ref())
+            .chain(self.page_model.as_ref())
             .flat_map(|model| model.fields.iter())
             .flat_map(|field| field.1.import.iter())
             .chain(argument_imports)
@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use regex::{Captures, Regex};
+
+use crate::{
+    error::Error,
+    request::{Parameter, Query},
+};
+
+use super::pagination::sql_type;
+
+/// Parameters marked with `@optional: status, author_id`, whose filters are
+/// dropped when the argument is `None`.
+pub struct OptionalFilters;
+
+impl OptionalFilters {
+    /// Rewrites every `column = $n` comparison of an optional parameter into
+    /// `($n::type is null or column = $n)`, and makes the parameter nullable.
+    pub fn apply(query: &Query) -> Result<Query, Error> {
+        let Some(annotation) = query.annotations.get("optional") else {
+            return Ok(query.clone());
+        };
+
+        let invalid = |reason: String| Error::InvalidAnnotation {
+            annotation: "optional".into(),
+            path: query.path.clone(),
+            line: annotation.line,
+            reason,
+        };
+
+        let value = annotation.value.as_deref().unwrap_or_default();
+        let mut sql = query.query.to_string();
+        let mut parameters = query.parameters.to_vec();
+
+        for name in value.split(',').map(str::trim) {
+            let Some(index) = parameters.iter().position(|param| &*param.name == name) else {
+                return Err(invalid(format!("unknown parameter \"{name}\"")));
+            };
+
+            let predicate = Self::predicate(index + 1);
+            let cast = Self::cast(&parameters[index], index + 1);
+
+            if !predicate.is_match(&sql) {
+                return Err(invalid(format!(
+                    "no column is compared with the parameter \"{name}\""
+                )));
+            }
+
+            if predicate
+                .captures_iter(&sql)
+                .any(|captures| captures.name("call").is_some())
+            {
+                return Err(invalid(format!(
+                    "the parameter \"{name}\" must be compared with a column, not an expression"
+                )));
+            }
+
+            sql = predicate
+                .replace_all(&sql, |captures: &Captures| {
+                    format!("({cast} is null or {})", &captures[0])
+                })
+                .into_owned();
+            parameters[index].not_null = false;
+        }
+
+        Ok(Query {
+            query: sql.into(),
+            parameters: parameters.into(),
+            ..query.clone()
+        })
+    }
+
+    /// Matches `column = $n`, `column = any($n)`, `$n = column` and `$n = any(column)`,
+    /// along with any cast of the parameter such as `$n::int`.
+    fn predicate(index: usize) -> Regex {
+        let identifier = r#"(?:[A-Za-z_]\w*|"[^"]+")"#;
+        let column = format!(r"{identifier}(?:\.{identifier})*");
+        let operator = r"(?:=|<>|!=|<=|>=|<|>|\bnot\s+i?like\b|\bi?like\b)";
+        let cast = format!(
+            r"(?:\s*::\s*{identifier}(?:\.{identifier})?(?:\s*\(\s*\d+(?:\s*,\s*\d+)*\s*\))?(?:\s*\[\s*\])*)?"
+        );
+        let parameter = format!(r"\${index}\b{cast}");
+
+        Regex::new(&format!(
+            r"(?i){column}\s*{operator}\s*(?:any\s*\(\s*{parameter}\s*\)|{parameter})|{parameter}\s*{operator}\s*(?:any\s*\(\s*{column}\s*\)|{column}(?P<call>\s*\()?)"
+        ))
+        .unwrap()
+    }
+
+    fn cast(parameter: &Parameter, index: usize) -> Rc<str> {
+        let mut sql_type = sql_type(&parameter.type_.schema, &parameter.type_.name);
+        if parameter.is_list {
+            sql_type.push_str("[]");
+        }
+        format!("${index}::{sql_type}").into()
+    }
+}
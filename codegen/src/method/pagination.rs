@@ -0,0 +1,185 @@
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    request::{Annotation, Catalog, OutputType, Query},
+    utils::quote_identifier,
+};
+
+/// Keyset pagination of a `:many` query, configured with
+/// `@paginate: column_a, column_b [desc]`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Pagination {
+    /// The fields of each row that make up the cursor.
+    columns: Vec<Rc<str>>,
+    descending: bool,
+    /// Whether each row is the only output column itself, which then is the cursor.
+    scalar: bool,
+    #[serde(skip)]
+    query: Rc<str>,
+}
+
+/// An ordering column of a wrapped query, and the type its cursor value is cast to.
+pub(super) struct Key {
+    pub expression: String,
+    pub sql_type: String,
+}
+
+impl Pagination {
+    pub fn from_query(query: &Query, catalog: &Catalog) -> Result<Option<Pagination>, Error> {
+        let Some(annotation) = query.annotations.get("paginate") else {
+            return Ok(None);
+        };
+
+        let invalid = |reason: String| Error::InvalidAnnotation {
+            annotation: "paginate".into(),
+            path: query.path.clone(),
+            line: annotation.line,
+            reason,
+        };
+
+        if &*query.command != "many" {
+            return Err(invalid("only :many queries can be paginated".into()));
+        }
+
+        let (columns, descending) = Self::parse(annotation).map_err(invalid)?;
+        let scalar = query.output.len() == 1 && !is_model(catalog, &query.output[0].type_);
+
+        let keys = columns
+            .iter()
+            .map(|column| key(query, catalog, "page", column))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                invalid("every column must be part of the output of the query".into())
+            })?;
+
+        if scalar && columns.len() != 1 {
+            return Err(invalid(
+                "a query with a single output column can only be paginated by it".into(),
+            ));
+        }
+
+        Ok(Some(Pagination {
+            query: Self::paginated_query(query, &keys, descending).into(),
+            columns,
+            descending,
+            scalar,
+        }))
+    }
+
+    /// The query with the cursor and page size parameters, which follow the query parameters.
+    pub fn query(&self) -> Rc<str> {
+        self.query.clone()
+    }
+
+    fn parse(annotation: &Annotation) -> Result<(Vec<Rc<str>>, bool), String> {
+        let value = annotation.value.as_deref().unwrap_or_default();
+        let mut columns = vec![];
+        let mut directions = vec![];
+
+        for column in value.split(',').map(str::trim) {
+            let mut words = column.split_whitespace();
+            let (Some(name), direction, None) = (words.next(), words.next(), words.next()) else {
+                return Err(format!("expected a list of columns, found \"{value}\""));
+            };
+
+            match direction.map(str::to_lowercase).as_deref() {
+                None | Some("asc") => directions.push(false),
+                Some("desc") => directions.push(true),
+                Some(direction) => return Err(format!("unknown direction \"{direction}\"")),
+            }
+            columns.push(name.into());
+        }
+
+        if directions.iter().any(|&desc| desc != directions[0]) {
+            return Err("every column must be sorted in the same direction".into());
+        }
+
+        Ok((columns, directions[0]))
+    }
+
+    fn paginated_query(query: &Query, keys: &[Key], descending: bool) -> String {
+        let cursor = query.parameters.len() + 1;
+        let limit = query.parameters.len() + 2;
+        let (operator, direction) = if descending {
+            ("<", " desc")
+        } else {
+            (">", "")
+        };
+
+        let expressions: Vec<_> = keys.iter().map(|key| key.expression.clone()).collect();
+        let cursor_values: Vec<_> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| format!("((${cursor}::text)::jsonb->>{i})::{}", key.sql_type))
+            .collect();
+        let order_by: Vec<_> = expressions
+            .iter()
+            .map(|expression| format!("{expression}{direction}"))
+            .collect();
+
+        format!(
+            "select page.* from (\n{}\n) as page\nwhere ${cursor}::text is null or ({}) {operator} ({})\norder by {}\nlimit ${limit}",
+            query.query.trim().trim_end_matches(';'),
+            expressions.join(", "),
+            cursor_values.join(", "),
+            order_by.join(", "),
+        )
+    }
+}
+
+/// The expression of `column` in the output of `query`, once wrapped as `alias`. The column may also
+/// be a field of a single model output column.
+pub(super) fn key(query: &Query, catalog: &Catalog, alias: &str, column: &str) -> Option<Key> {
+    if let Some(output) = query.output.iter().find(|output| &*output.name == column) {
+        return Some(Key {
+            expression: format!("{alias}.{}", quote_identifier(column)),
+            sql_type: sql_type(&output.type_.schema, &output.type_.name),
+        });
+    }
+
+    let [output] = &*query.output else {
+        return None;
+    };
+
+    let field = catalog
+        .schemas
+        .iter()
+        .find(|schema| schema.name == output.type_.schema)?
+        .models
+        .iter()
+        .find(|model| model.name == output.type_.name)?
+        .columns
+        .iter()
+        .find(|field| &*field.name == column)?;
+
+    let type_name = if field.type_field.array_dimensions > 0 {
+        format!("_{}", field.type_field.name)
+    } else {
+        field.type_field.name.to_string()
+    };
+
+    Some(Key {
+        expression: format!(
+            "({alias}.{}).{}",
+            quote_identifier(&output.name),
+            quote_identifier(column)
+        ),
+        sql_type: sql_type(&field.type_field.schema_name, &type_name),
+    })
+}
+
+fn is_model(catalog: &Catalog, type_: &OutputType) -> bool {
+    catalog
+        .schemas
+        .iter()
+        .filter(|schema| schema.name == type_.schema)
+        .flat_map(|schema| schema.models.iter())
+        .any(|model| model.name == type_.name)
+}
+
+pub(super) fn sql_type(schema: &str, name: &str) -> String {
+    format!("{}.{}", quote_identifier(schema), quote_identifier(name))
+}
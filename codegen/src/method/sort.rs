@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    request::{Catalog, Query},
+};
+
+use super::pagination::key;
+
+/// Caller chosen ordering of a `:many` or `:iter` query, configured with
+/// `@sort: column_a, column_b`. Since `order by` cannot be parameterized,
+/// a query is precompiled for every column and direction.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Sort {
+    columns: Vec<SortColumn>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SortColumn {
+    name: Rc<str>,
+    ascending: Rc<str>,
+    descending: Rc<str>,
+}
+
+impl Sort {
+    pub fn from_query(query: &Query, catalog: &Catalog) -> Result<Option<Sort>, Error> {
+        let Some(annotation) = query.annotations.get("sort") else {
+            return Ok(None);
+        };
+
+        let invalid = |reason: String| Error::InvalidAnnotation {
+            annotation: "sort".into(),
+            path: query.path.clone(),
+            line: annotation.line,
+            reason,
+        };
+
+        if !matches!(&*query.command, "many" | "iter") {
+            return Err(invalid("only :many and :iter queries can be sorted".into()));
+        }
+
+        if query.annotations.contains_key("paginate") {
+            return Err(invalid(
+                "a paginated query is already sorted by its cursor".into(),
+            ));
+        }
+
+        let value = annotation.value.as_deref().unwrap_or_default();
+        let mut columns: Vec<SortColumn> = vec![];
+
+        for name in value.split(',').map(str::trim) {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(invalid(format!(
+                    "expected a list of columns, found \"{value}\""
+                )));
+            }
+
+            if columns.iter().any(|column| &*column.name == name) {
+                return Err(invalid(format!("the column \"{name}\" is repeated")));
+            }
+
+            let key = key(query, catalog, "sorted", name).ok_or_else(|| {
+                invalid(format!(
+                    "the column \"{name}\" is not part of the output of the query"
+                ))
+            })?;
+
+            columns.push(SortColumn {
+                name: name.into(),
+                ascending: Self::sorted_query(query, &key.expression).into(),
+                descending: Self::sorted_query(query, &format!("{} desc", key.expression)).into(),
+            });
+        }
+
+        Ok(Some(Sort { columns }))
+    }
+
+    fn sorted_query(query: &Query, order_by: &str) -> String {
+        format!(
+            "select sorted.* from (\n{}\n) as sorted\norder by {order_by}",
+            query.query.trim().trim_end_matches(';'),
+        )
+    }
+}
@@ -1 +1,60 @@
+use std::rc::Rc;
 
+use serde::{Deserialize, Serialize};
+
+use crate::request::Query;
+
+/// How the in-memory fake of a method behaves, for targets that generate
+/// fake query classes with the `fake_queries` option. Fakes record every
+/// call, and return the values programmed with their `expect_` helpers,
+/// or a default value when there are none left.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Fake {
+    kind: FakeKind,
+    /// The arguments of the method, as they are recorded for each call.
+    arguments: Vec<Rc<str>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeKind {
+    /// A row or value that defaults to `None`.
+    Optional,
+    /// A row or value of a `@not_null_result` query, for which an unexpected call is an error.
+    Required,
+    /// A list of rows that defaults to an empty list.
+    Rows,
+    /// A page of rows that defaults to an empty page.
+    Page,
+    /// A mapping of rows by key that defaults to an empty mapping.
+    Keyed,
+    /// An affected row count that defaults to zero.
+    Count,
+    /// Nothing is returned.
+    Nothing,
+}
+
+impl Fake {
+    pub fn new(query: &Query, arguments: Vec<Rc<str>>, paginated: bool) -> Fake {
+        let kind = match &*query.command {
+            "one" | "val" if query.annotations.contains_key("not_null_result") => {
+                FakeKind::Required
+            }
+            "one" | "val" => FakeKind::Optional,
+            "many" if paginated => FakeKind::Page,
+            "many" | "iter" => FakeKind::Rows,
+            "batch" if !query.output.is_empty() => FakeKind::Rows,
+            "execrows" | "copyfrom" => FakeKind::Count,
+            _ => FakeKind::Nothing,
+        };
+        Fake { kind, arguments }
+    }
+
+    /// The fake of a method whose rows are returned by key.
+    pub fn keyed(self) -> Fake {
+        Fake {
+            kind: FakeKind::Keyed,
+            ..self
+        }
+    }
+}
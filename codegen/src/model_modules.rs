@@ -8,14 +8,16 @@ use serde::Serialize;
 use crate::{
     error::Error,
     r#type::Type,
-    request::{Enum, Query, Request, Schema},
-    type_builder::{self, TypeBuilder},
+    request::{Catalog, Column, Model, Request, Schema},
+    type_builder::TypeBuilder,
 };
 
 #[derive(Clone, Serialize)]
 pub struct ModelModules {
     type_builder: TypeBuilder,
     pub model_modules: BTreeMap<Rc<str>, ModelModule>,
+    /// The output models shared by queries, with the `deduplicate_rows` option.
+    pub rows: Option<ModelModule>,
 }
 
 #[derive(Clone, Serialize)]
@@ -23,13 +25,32 @@ pub struct ModelModule {
     type_builder: TypeBuilder,
     pub imports: Vec<Rc<str>>,
     pub classes: Vec<ModelClass>,
-    pub enums: Rc<[Enum]>,
+    pub enums: Vec<ModelEnum>,
 }
 
 #[derive(Clone, Serialize)]
 pub struct ModelClass {
     r#type: Type,
     fields: Vec<(Rc<str>, Type)>,
+    /// The comment of the table or type, and of its columns.
+    doc: Option<Rc<str>>,
+    field_docs: BTreeMap<Rc<str>, Rc<str>>,
+    /// The placeholder values of the factory of the model, with the `model_factories` option.
+    factory: Vec<FactoryField>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModelEnum {
+    r#type: Type,
+    values: Rc<[Rc<str>]>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FactoryField {
+    name: Rc<str>,
+    value: Rc<str>,
+    /// Whether the value creates a parent row, so it is only evaluated if it is not overridden.
+    lazy: bool,
 }
 
 impl ModelModule {
@@ -41,14 +62,35 @@ impl ModelModule {
             enums: Default::default(),
         }
     }
+
+    pub fn rows(type_builder: TypeBuilder, classes: Vec<ModelClass>) -> Self {
+        ModelModule {
+            classes,
+            ..ModelModule::new(type_builder)
+        }
+    }
 }
 
 impl ModelClass {
+    /// A model that is not backed by a table or type, so it has no factory.
+    pub fn row(r#type: Type, fields: Vec<(Rc<str>, Type)>) -> Self {
+        ModelClass {
+            r#type,
+            fields,
+            doc: None,
+            field_docs: Default::default(),
+            factory: vec![],
+        }
+    }
+
+    pub fn declaration(&self) -> &Rc<str> {
+        &self.r#type.declaration
+    }
+
     fn imports(&self) -> impl Iterator<Item = Rc<str>> + '_ {
         self.fields
             .iter()
-            .map(|(_, ty)| ty.import.iter())
-            .flatten()
+            .flat_map(|(_, ty)| ty.import.iter())
             .cloned()
     }
 }
@@ -69,6 +111,7 @@ impl ModelModules {
         let mut modules = ModelModules {
             type_builder,
             model_modules: Default::default(),
+            rows: None,
         };
 
         for schema in request.catalog.schemas.iter() {
@@ -78,6 +121,10 @@ impl ModelModules {
         Ok(modules)
     }
 
+    pub fn type_builder(&self) -> &TypeBuilder {
+        &self.type_builder
+    }
+
     pub fn add_schema(&mut self, schema: &Schema) {
         let mut module = ModelModule::new(self.type_builder.clone());
 
@@ -89,12 +136,86 @@ impl ModelModules {
                     .iter()
                     .map(|column| (column.name.clone(), self.type_builder.from_col(column)))
                     .collect::<Vec<_>>(),
+                doc: model.comment.clone(),
+                field_docs: model
+                    .columns
+                    .iter()
+                    .filter_map(|column| Some((column.name.clone(), column.comment.clone()?)))
+                    .collect(),
+                factory: model
+                    .columns
+                    .iter()
+                    .map(|column| self.factory_field(schema, model, column))
+                    .collect(),
             };
             module.classes.push(model_class);
         }
 
-        module.enums = schema.enums.clone();
+        module.enums = schema
+            .enums
+            .iter()
+            .map(|enum_| ModelEnum {
+                r#type: self.type_builder.composite(&schema.name, &enum_.name),
+                values: enum_.values.clone(),
+            })
+            .collect();
 
         self.model_modules.insert(schema.name.clone(), module);
     }
+
+    /// A required foreign key is filled in with the referenced column of a row
+    /// created by the factory of the parent model, unless it has a constant default.
+    fn factory_field(&self, schema: &Schema, model: &Model, column: &Column) -> FactoryField {
+        let catalog = self.type_builder.catalog();
+        let parent = catalog
+            .parent(column)
+            .filter(|_| !column.is_nullable && column.default.is_none())
+            .filter(|(parent_schema, parent, _)| {
+                !Self::creates(catalog, parent_schema, parent, (schema, model), &mut vec![])
+            });
+
+        match parent {
+            Some((parent_schema, parent, parent_column)) => FactoryField {
+                name: column.name.clone(),
+                value: self.type_builder.foreign_key_sample(
+                    &parent_schema.name,
+                    &parent.name,
+                    &parent_column.name,
+                ),
+                lazy: true,
+            },
+            None => FactoryField {
+                name: column.name.clone(),
+                value: self.type_builder.sample(column),
+                lazy: false,
+            },
+        }
+    }
+
+    /// Whether the factory of `from` creates a row of `model`, by following the
+    /// required foreign keys. A self reference, or a cycle of references, is
+    /// filled in with a sample value instead, as the factories would never return.
+    fn creates<'a>(
+        catalog: &'a Catalog,
+        from_schema: &'a Schema,
+        from: &'a Model,
+        (schema, model): (&Schema, &Model),
+        visited: &mut Vec<(&'a Rc<str>, &'a Rc<str>)>,
+    ) -> bool {
+        if (&from_schema.name, &from.name) == (&schema.name, &model.name) {
+            return true;
+        }
+        if visited.contains(&(&from_schema.name, &from.name)) {
+            return false;
+        }
+        visited.push((&from_schema.name, &from.name));
+
+        from.columns
+            .iter()
+            .filter(|column| !column.is_nullable && column.default.is_none())
+            .filter_map(|column| catalog.parent(column))
+            .any(|(parent_schema, parent, _)| {
+                Self::creates(catalog, parent_schema, parent, (schema, model), visited)
+            })
+    }
 }
@@ -0,0 +1,231 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
+
+use crate::{
+    error::Error, file_generator::Layout, method::Method, model_modules::ModelModules,
+    naming::Identifiers, query_namespace::QueryNamespace, response::Diagnostic,
+    utils::to_snake_case,
+};
+
+/// Finds generated identifiers that would shadow each other, before any file is rendered.
+/// Names are compared as generated identifiers, after they are renamed with the
+/// `naming` option and reserved words are escaped.
+pub struct NameCollisions<'a> {
+    diagnostics: Vec<Diagnostic>,
+    identifiers: &'a Identifiers,
+    /// Whether the types of every namespace are declared in the same module.
+    single_file: bool,
+}
+
+impl<'a> NameCollisions<'a> {
+    pub fn check(
+        namespace: &QueryNamespace,
+        model_modules: &ModelModules,
+        identifiers: &'a Identifiers,
+        layout: Layout,
+    ) -> Result<(), Error> {
+        let mut collisions = NameCollisions {
+            diagnostics: vec![],
+            identifiers,
+            single_file: layout == Layout::SingleFile,
+        };
+        collisions.namespace(namespace, &mut BTreeMap::new());
+        collisions.model_modules(model_modules);
+
+        if collisions.diagnostics.is_empty() {
+            return Ok(());
+        }
+        Err(Error::NameCollisions(collisions.diagnostics))
+    }
+
+    fn namespace<'n>(
+        &mut self,
+        namespace: &'n QueryNamespace,
+        types: &mut BTreeMap<&'n str, &'n Method>,
+    ) {
+        let mut methods: BTreeMap<String, &Method> = BTreeMap::new();
+
+        for method in namespace.methods.iter() {
+            let name = &*method.query().name;
+            let identifier = self.identifiers.method(name);
+            match methods.insert(identifier.clone(), method) {
+                // Hand-written queries come first, so the clash is reported where it can be fixed.
+                Some(other) if other.generated_by().is_none() && method.generated_by().is_some() => {
+                    self.report(
+                        other,
+                        format!(
+                            "the query \"{}\" is generated as the method \"{identifier}\", which is also generated {}",
+                            other.query().name,
+                            Self::defined(method)
+                        ),
+                    )
+                }
+                Some(other) if other.query().name == method.query().name => self.report(
+                    method,
+                    format!(
+                        "the query \"{name}\" is already defined in {} {}",
+                        Self::namespace_name(namespace),
+                        Self::defined(other)
+                    ),
+                ),
+                Some(other) => self.report(
+                    method,
+                    format!(
+                        "the query \"{name}\" is generated as the method \"{identifier}\" of the query \"{}\" {}",
+                        other.query().name,
+                        Self::defined(other)
+                    ),
+                ),
+                None => (),
+            }
+
+            self.fields(method);
+
+            if namespace.subnamespaces.contains_key(&*identifier) {
+                self.report(
+                    method,
+                    format!(
+                        "the query \"{name}\" has the same name as the namespace \"{}\"",
+                        Self::child_path(namespace, name)
+                    ),
+                );
+            }
+
+            for ty in method.declared_types() {
+                let declaration = &*ty.declaration;
+                match types.get(declaration) {
+                    Some(other) if method.names_model(declaration) && other.names_model(declaration) => self.report(
+                        method,
+                        format!(
+                            "the model \"{declaration}\" of the query \"{name}\" has different fields than the one of the query \"{}\" {}",
+                            other.query().name,
+                            Self::defined(other)
+                        ),
+                    ),
+                    Some(other) => self.report(
+                        method,
+                        format!(
+                            "the type \"{declaration}\" of the query \"{name}\" is already declared for the query \"{}\" {}",
+                            other.query().name,
+                            Self::defined(other)
+                        ),
+                    ),
+                    None => {
+                        types.insert(declaration, method);
+                    }
+                }
+            }
+        }
+
+        for subnamespace in namespace.subnamespaces.values() {
+            if self.single_file {
+                self.namespace(subnamespace, types);
+            } else {
+                self.namespace(subnamespace, &mut BTreeMap::new());
+            }
+        }
+    }
+
+    /// The columns of a query that are generated as the same field of its output model.
+    fn fields(&mut self, method: &Method) {
+        let Some(model) = method.output_model() else {
+            return;
+        };
+        let mut fields: BTreeMap<String, &str> = BTreeMap::new();
+        for field in model.fields().keys() {
+            let identifier = self.identifiers.field(field);
+            if let Some(other) = fields.insert(identifier.clone(), field) {
+                self.report(
+                    method,
+                    format!(
+                        "the columns \"{other}\" and \"{field}\" of the query \"{}\" are both generated as the field \"{identifier}\"",
+                        method.query().name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn model_modules(&mut self, model_modules: &ModelModules) {
+        let mut modules: BTreeMap<String, &Rc<str>> = BTreeMap::new();
+
+        for schema in model_modules.model_modules.keys() {
+            let module = to_snake_case(schema);
+            if let Some(other) = modules.insert(module.clone(), schema) {
+                self.diagnostics.push(Diagnostic {
+                    path: Default::default(),
+                    line: 0,
+                    message: format!(
+                        "the schemas \"{other}\" and \"{schema}\" are both generated as the module \"models.{module}\""
+                    ),
+                });
+            }
+        }
+
+        let Some(rows) = &model_modules.rows else {
+            return;
+        };
+        if let Some(schema) = modules.get("rows") {
+            self.diagnostics.push(Diagnostic {
+                path: Default::default(),
+                line: 0,
+                message: format!(
+                    "the schema \"{schema}\" is generated as the module \"models.rows\" of the shared row models"
+                ),
+            });
+        }
+        let mut declarations = BTreeSet::new();
+        for class in rows.classes.iter() {
+            let declaration = class.declaration();
+            if !declarations.insert(declaration) {
+                self.diagnostics.push(Diagnostic {
+                    path: Default::default(),
+                    line: 0,
+                    message: format!(
+                        "the row models shared as \"models.rows.{declaration}\" have different fields, name one of them with @returns"
+                    ),
+                });
+            }
+        }
+    }
+
+    fn report(&mut self, method: &Method, message: String) {
+        let query = method.query();
+        self.diagnostics.push(Diagnostic {
+            path: query.path.clone(),
+            line: Self::line(method),
+            message,
+        });
+    }
+
+    fn line(method: &Method) -> i64 {
+        let query = method.query();
+        query.annotations.get("name").map_or(0, |name| name.line)
+    }
+
+    /// Where another method comes from, which is either a query file or an option
+    /// that derives it from the catalog.
+    fn defined(method: &Method) -> String {
+        let query = method.query();
+        if let Some(option) = method.generated_by() {
+            return format!("by the {option} option");
+        }
+        format!("at \"{}:{}\"", query.path, Self::line(method))
+    }
+
+    fn namespace_name(namespace: &QueryNamespace) -> String {
+        if namespace.path.is_empty() {
+            return "the root namespace".into();
+        }
+        format!("the namespace \"{}\"", namespace.path)
+    }
+
+    fn child_path(namespace: &QueryNamespace, name: &str) -> String {
+        if namespace.path.is_empty() {
+            return name.into();
+        }
+        format!("{}.{name}", namespace.path)
+    }
+}
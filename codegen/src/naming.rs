@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    file_gen_config::FileGenConfig,
+    request::Request,
+    utils::{to_camel_case, to_pascal_case, to_screaming_snake_case, to_snake_case},
+};
+
+/// Naming conventions of the generated identifiers, configured with the
+/// `naming` option. Names sent to or read from the database are not renamed.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Naming {
+    /// The case of declared classes, which otherwise follows the target.
+    pub class_case: Option<Case>,
+    pub field_case: Option<Case>,
+    pub method_case: Option<Case>,
+    /// The suffix of the output model of a query with many columns.
+    pub row_suffix: Option<String>,
+    /// Class names of tables and types, such as `tbl_usr: user`.
+    pub tables: BTreeMap<String, String>,
+    /// Field names of columns, such as `usr_name: name`.
+    pub columns: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Case {
+    Snake,
+    Camel,
+    Pascal,
+    ScreamingSnake,
+}
+
+impl Case {
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Case::Snake => to_snake_case(name),
+            Case::Camel => to_camel_case(name),
+            Case::Pascal => to_pascal_case(name),
+            Case::ScreamingSnake => to_screaming_snake_case(name),
+        }
+    }
+
+    /// The template that renders a `name` in this case.
+    pub fn template(self) -> &'static str {
+        match self {
+            Case::Snake => "{{ name | to_snake_case }}",
+            Case::Camel => "{{ name | to_camel_case }}",
+            Case::Pascal => "{{ name | to_pascal_case }}",
+            Case::ScreamingSnake => "{{ name | to_screaming_snake_case }}",
+        }
+    }
+}
+
+impl Naming {
+    pub fn new(request: &Request) -> Result<Naming, Error> {
+        Ok(request
+            .config
+            .codegen
+            .try_option("naming")?
+            .unwrap_or_default())
+    }
+
+    pub fn table<'a>(&'a self, name: &'a str) -> &'a str {
+        self.tables.get(name).map_or(name, |name| name)
+    }
+
+    pub fn field(&self, name: &str) -> String {
+        let name = self.columns.get(name).map_or(name, |name| &**name);
+        match self.field_case {
+            Some(case) => case.apply(name),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn method(&self, name: &str) -> String {
+        match self.method_case {
+            Some(case) => case.apply(name),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn row_suffix(&self) -> &str {
+        self.row_suffix.as_deref().unwrap_or("row")
+    }
+}
+
+/// The identifiers of the generated code, renamed with the naming conventions
+/// and escaped when they are reserved words of the target.
+#[derive(Clone)]
+pub struct Identifiers {
+    config: FileGenConfig,
+    naming: Naming,
+}
+
+impl Identifiers {
+    pub fn new(config: FileGenConfig, naming: Naming) -> Self {
+        Identifiers { config, naming }
+    }
+
+    pub fn escape(&self, identifier: &str) -> String {
+        self.config.escape_identifier(identifier)
+    }
+
+    pub fn field(&self, name: &str) -> String {
+        self.escape(&self.naming.field(name))
+    }
+
+    pub fn method(&self, name: &str) -> String {
+        self.escape(&self.naming.method(name))
+    }
+
+    /// A parameter is either an argument or a field of a record argument, such as `book.title`.
+    pub fn parameter(&self, identifier: &str) -> String {
+        match identifier.split_once('.') {
+            Some((argument, field)) => format!("{}.{}", self.escape(argument), self.field(field)),
+            None => self.escape(identifier),
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     rc::Rc,
 };
 
-use crate::{error::Error, method::Method, request::Request, utils::to_pascal_case};
+use crate::{error::Error, method::Method, request::Request, response::Diagnostic};
 pub use query_namespace_builder::QueryNamespaceBuilder;
 use serde::{Deserialize, Serialize};
 mod query_namespace_builder;
@@ -11,18 +11,21 @@ mod query_namespace_builder;
 #[derive(Serialize, Deserialize)]
 pub struct QueryNamespace {
     pub name: String,
+    /// The dotted path of the namespace from the root, which is empty.
+    pub path: String,
     pub subnamespaces: BTreeMap<Rc<str>, QueryNamespace>,
     pub methods: Vec<Method>,
 }
 
 impl QueryNamespace {
     pub fn from_request(request: &Request) -> Result<Self, Error> {
-        Ok(QueryNamespaceBuilder::new(request)?.build())
+        QueryNamespaceBuilder::new(request)?.build()
     }
 
     fn root() -> QueryNamespace {
         QueryNamespace {
             name: String::new(),
+            path: String::new(),
             subnamespaces: Default::default(),
             methods: Default::default(),
         }
@@ -35,6 +38,28 @@ impl QueryNamespace {
             .collect()
     }
 
+    /// Whether a method of this namespace or its subnamespaces is paginated.
+    pub fn paginated_tree(&self) -> bool {
+        self.methods.iter().any(|method| method.paginated())
+            || self
+                .subnamespaces
+                .values()
+                .any(|subnamespace| subnamespace.paginated_tree())
+    }
+
+    /// The diagnostics of the methods of this namespace and its subnamespaces.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let subnamespaces = self
+            .subnamespaces
+            .values()
+            .flat_map(|subnamespace| subnamespace.diagnostics());
+        self.methods
+            .iter()
+            .filter_map(Method::diagnostic)
+            .chain(subnamespaces)
+            .collect()
+    }
+
     pub fn resolve(&mut self, name: &str) -> &mut QueryNamespace {
         self._resolve(&name.split('.').collect::<Vec<_>>())
     }
@@ -44,14 +69,20 @@ impl QueryNamespace {
             return self;
         }
 
+        let path = if self.path.is_empty() {
+            name[0].to_string()
+        } else {
+            format!("{}.{}", self.path, name[0])
+        };
         let entry = self.subnamespaces.entry(name[0].into());
 
         let namespace = entry.or_insert_with(|| QueryNamespace {
             name: name[0].into(),
+            path,
             methods: Default::default(),
             subnamespaces: Default::default(),
         });
 
-        return namespace._resolve(&name[1..]);
+        namespace._resolve(&name[1..])
     }
 }
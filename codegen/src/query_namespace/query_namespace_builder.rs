@@ -1,6 +1,7 @@
 use crate::{
+    catalog_queries::CatalogQueries,
     error::Error,
-    method::MethodBuilder,
+    method::{Method, MethodBuilder},
     query_namespace::QueryNamespace,
     request::{Query, Request},
     type_builder::TypeBuilder,
@@ -22,18 +23,34 @@ impl QueryNamespaceBuilder {
         })
     }
 
-    pub fn build(&mut self) -> QueryNamespace {
+    pub fn build(&mut self) -> Result<QueryNamespace, Error> {
         for query in self.request.queries.clone().iter() {
-            self.include_query(query);
+            self.include_query(query)?;
         }
 
-        std::mem::replace(&mut self.namespace, QueryNamespace::root())
+        for catalog_query in CatalogQueries::from_request(&self.request) {
+            let method = self.method_builder.build_catalog_query(&catalog_query)?;
+            self.include_method(catalog_query.query.namespace(), method);
+        }
+
+        Ok(std::mem::replace(
+            &mut self.namespace,
+            QueryNamespace::root(),
+        ))
     }
 
-    pub fn include_query(&mut self, query: &Query) {
-        let name = query.namespace();
+    pub fn include_query(&mut self, query: &Query) -> Result<(), Error> {
+        let method = self.method_builder.build(query)?;
+        self.include_method(query.namespace(), method);
+        Ok(())
+    }
+
+    fn include_method(&mut self, name: &str, mut method: Method) {
         let namespace = self.namespace.resolve(name);
-        namespace.methods.push(self.method_builder.build(query));
+        for other in namespace.methods.iter() {
+            method.share_models(other);
+        }
+        namespace.methods.push(method);
     }
 }
 
@@ -44,14 +61,13 @@ impl Query {
             .get("namespace")
             .and_then(|n| n.value.as_deref())
         {
-            return &*namespace;
+            return namespace;
         }
 
-        return self
-            .path
+        self.path
             .split('/')
-            .last()
+            .next_back()
             .unwrap()
-            .trim_end_matches(".sql");
+            .trim_end_matches(".sql")
     }
 }
@@ -1,12 +1,13 @@
 use std::collections::BTreeMap;
-use std::collections::HashMap;
-use std::iter::Map;
 use std::rc::Rc;
 
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 
+use crate::error::Error;
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Request {
     pub catalog: Catalog,
@@ -19,6 +20,43 @@ pub struct Catalog {
     pub schemas: Rc<[Schema]>,
 }
 
+impl Catalog {
+    /// The schema, model and column referenced by a single column foreign key.
+    /// When the key doesn't name the column, it's the primary key of the parent.
+    pub fn parent(&self, column: &Column) -> Option<(&Schema, &Model, &Column)> {
+        if !column.is_foreign_key || column.type_field.array_dimensions > 0 {
+            return None;
+        }
+
+        let parent_schema_name = column.foreign_table_schema.as_ref()?;
+        let parent_name = column.foreign_table_name.as_ref()?;
+
+        // Table backed enums are removed from the models, so they are skipped here.
+        let parent_schema = self
+            .schemas
+            .iter()
+            .find(|schema| &schema.name == parent_schema_name)?;
+        let parent = parent_schema
+            .models
+            .iter()
+            .find(|model| &model.name == parent_name)?;
+
+        let parent_column = match &column.foreign_column_name {
+            Some(name) => parent.columns.iter().find(|column| &column.name == name)?,
+            None => {
+                let mut primary_keys = parent.columns.iter().filter(|c| c.is_primary_key);
+                let primary_key = primary_keys.next()?;
+                if primary_keys.next().is_some() {
+                    return None;
+                }
+                primary_key
+            }
+        };
+
+        Some((parent_schema, parent, parent_column))
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Schema {
     pub name: Rc<str>,
@@ -37,6 +75,9 @@ pub struct Model {
     pub kind: Rc<str>,
     pub name: Rc<str>,
     pub columns: Rc<[Column]>,
+    /// Set with `comment on table`, or `comment on type` for composite types.
+    #[serde(default)]
+    pub comment: Option<Rc<str>>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,12 +86,18 @@ pub struct Column {
     #[serde(rename = "type")]
     pub type_field: ColumnType,
     pub default: Option<Rc<str>>,
+    /// Only set for single column unique constraints.
     pub is_unique: bool,
     pub is_nullable: bool,
+    /// Only set for single column foreign keys.
     pub is_foreign_key: bool,
     pub is_primary_key: bool,
     pub foreign_table_name: Option<Rc<str>>,
     pub foreign_table_schema: Option<Rc<str>>,
+    pub foreign_column_name: Option<Rc<str>>,
+    /// Set with `comment on column`.
+    #[serde(default)]
+    pub comment: Option<Rc<str>>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -70,6 +117,9 @@ pub struct Query {
     pub command: Rc<str>,
     pub path: Rc<str>,
     pub annotations: Rc<BTreeMap<String, Annotation>>,
+    /// The comment block above the query, without its annotations.
+    #[serde(default)]
+    pub comment: Option<Rc<str>>,
     pub output: Rc<[OutputColumn]>,
     pub parameters: Rc<[Parameter]>,
 }
@@ -100,6 +150,9 @@ pub struct Parameter {
     #[serde(rename = "type")]
     pub type_: OutputType,
     pub not_null: bool,
+    /// Whether the parameter was declared as a list, and is described by its element type.
+    #[serde(default)]
+    pub is_list: bool,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -118,9 +171,37 @@ pub struct Codegen {
     pub options: Value,
 }
 
+impl Codegen {
+    /// Reads a target specific option from `codegen.options`, returning `None`
+    /// if it is missing or has an unexpected shape.
+    pub fn option<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        T::deserialize(self.options.get(name)?).ok()
+    }
+
+    /// Reads a target specific option from `codegen.options`, returning `None`
+    /// if it is missing and an error if it has an unexpected shape.
+    pub fn try_option<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, Error> {
+        let Some(value) = self.options.get(name) else {
+            return Ok(None);
+        };
+        T::deserialize(value)
+            .map(Some)
+            .map_err(|error| Error::InvalidOption {
+                name: name.into(),
+                reason: error.to_string(),
+            })
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TypeConfig {
     pub annotation: Rc<str>,
     #[serde(default)]
     pub import: Rc<[Rc<str>]>,
+    /// A placeholder value used by model factories.
+    #[serde(default)]
+    pub sample: Option<Rc<str>>,
+    /// Renders a constant column default as a value of this type.
+    #[serde(default)]
+    pub literal: Option<Rc<str>>,
 }
@@ -1,8 +1,14 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+};
+
 use serde::Serialize;
 
 #[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Response {
     pub files: Vec<File>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -10,3 +16,20 @@ pub struct File {
     pub path: String,
     pub content: String,
 }
+
+/// A warning about a query, which the host reports without failing the build.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    pub path: Rc<str>,
+    pub line: i64,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "\"{}:{}\" {}", self.path, self.line, self.message)
+    }
+}
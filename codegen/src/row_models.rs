@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use indexmap::IndexMap;
+
+use crate::{
+    method::{Method, MethodModel},
+    model_modules::{ModelClass, ModelModule},
+    query_namespace::QueryNamespace,
+    request::Request,
+    type_builder::TypeBuilder,
+};
+
+/// With the `deduplicate_rows` option, the output models of queries with the
+/// same field names and types are declared once, in the `models.rows` module.
+/// A shared model is named after the output model that sorts first, so that
+/// reordering the queries does not rename it.
+pub struct RowModels;
+
+/// The field names and type annotations of an output model, in any order.
+type Shape = Vec<(Rc<str>, Rc<str>)>;
+
+impl RowModels {
+    pub fn deduplicate(
+        request: &Request,
+        namespace: &mut QueryNamespace,
+        type_builder: &TypeBuilder,
+    ) -> Option<ModelModule> {
+        if !request
+            .config
+            .codegen
+            .option("deduplicate_rows")
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let mut methods = vec![];
+        Self::collect(namespace, &mut methods);
+
+        let mut shapes: IndexMap<Shape, (MethodModel, Vec<&mut Method>)> = IndexMap::new();
+        for method in methods {
+            let Some((shape, model)) = Self::shape(method) else {
+                continue;
+            };
+            let model = model.clone();
+            let (first, methods) = shapes
+                .entry(shape)
+                .or_insert_with(|| (model.clone(), vec![]));
+            if model.r#type().declaration < first.r#type().declaration {
+                *first = model;
+            }
+            methods.push(method);
+        }
+
+        let mut classes = vec![];
+        for (model, methods) in shapes
+            .into_values()
+            .filter(|(_, methods)| methods.len() > 1)
+        {
+            let row = type_builder.row_model(&model.r#type().declaration);
+            let items = type_builder.array(row.clone(), 1);
+            let fields = model
+                .fields()
+                .iter()
+                .map(|(name, ty)| (name.clone(), ty.clone()))
+                .collect();
+            classes.push(ModelClass::row(row.clone(), fields));
+
+            for method in methods {
+                method.use_row_model(row.clone(), items.clone());
+            }
+        }
+
+        if classes.is_empty() {
+            return None;
+        }
+        classes.sort_by(|a, b| a.declaration().cmp(b.declaration()));
+        Some(ModelModule::rows(type_builder.clone(), classes))
+    }
+
+    fn collect<'a>(namespace: &'a mut QueryNamespace, methods: &mut Vec<&'a mut Method>) {
+        methods.extend(namespace.methods.iter_mut());
+        for subnamespace in namespace.subnamespaces.values_mut() {
+            Self::collect(subnamespace, methods);
+        }
+    }
+
+    /// Models named with `@returns` are kept where they are declared.
+    fn shape(method: &Method) -> Option<(Shape, &MethodModel)> {
+        let model = method.output_model().filter(|model| !model.named())?;
+        let mut shape: Shape = model
+            .fields()
+            .iter()
+            .map(|(name, ty)| (name.clone(), ty.annotation.clone()))
+            .collect();
+        shape.sort();
+        Some((shape, model))
+    }
+}
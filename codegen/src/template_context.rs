@@ -1,8 +1,5 @@
-use crate::{
-    query_namespace::QueryNamespace,
-    request::{Model, Query},
-};
+use crate::query_namespace::QueryNamespace;
 
 pub struct TemplateContext {
-    namespaces: QueryNamespace,
+    pub namespaces: QueryNamespace,
 }
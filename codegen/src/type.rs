@@ -1,13 +1,6 @@
-use std::{collections::BTreeMap, rc::Rc, sync::LazyLock};
+use std::rc::Rc;
 
 use serde::Deserialize;
-use serde_json::json;
-
-use crate::{
-    error::Error,
-    request::{self, Column, OutputType, TypeConfig},
-    utils::render,
-};
 
 #[derive(serde::Serialize, Deserialize, Clone, Debug)]
 pub struct Type {
@@ -1,10 +1,12 @@
-use std::{collections::BTreeMap, rc::Rc, sync::LazyLock};
+use std::{collections::BTreeMap, rc::Rc, sync::OnceLock};
 
-use minijinja::context;
+use minijinja::{context, Value};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::Error,
+    naming::Naming,
     r#type::Type,
     request::{Catalog, Column, ColumnType, OutputType, Request, TypeConfig},
     utils::render,
@@ -18,6 +20,7 @@ pub struct TypeBuilder {
     enums: Vec<(Rc<str>, Rc<str>)>,
     catalog: Catalog,
     type_map: TypeMap,
+    naming: Naming,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -25,9 +28,29 @@ pub struct TypeMap {
     new_type_case: String,
     null: Type,
     array: Type,
+    tuple: Type,
+    iterable: Type,
+    mapping: Type,
     composite: Type,
     wildcard: TypeConfig,
     schema: BTreeMap<String, BTreeMap<String, TypeConfig>>,
+    #[serde(default)]
+    sample: SampleMap,
+}
+
+/// Templates of the placeholder values of types that are not configured per
+/// SQL type, used by model factories.
+#[derive(Deserialize, Serialize, Clone, Default)]
+struct SampleMap {
+    null: String,
+    array: String,
+    /// A row created by the factory of the model.
+    composite: String,
+    /// A member of an enum, given one of its values.
+    #[serde(rename = "enum")]
+    enum_: String,
+    /// A field of a row created by a parent factory.
+    foreign_key: String,
 }
 
 impl TypeBuilder {
@@ -45,22 +68,30 @@ impl TypeBuilder {
                     .map(move |enum_| (schema.name.clone(), enum_.name.clone()))
             })
             .collect();
-        let resolver = match &*lang {
-            "python:asyncpg" => TypeBuilder {
-                type_overrides,
-                enums,
-                catalog: request.catalog.clone(),
-                type_map: PYTHON_ASYNCPG.clone(),
-            },
-            "python:psycopg" => TypeBuilder {
-                type_overrides,
-                enums,
-                catalog: request.catalog.clone(),
-                type_map: PYTHON_PSYCOPG.clone(),
-            },
+        let mut type_map = match &*lang {
+            "python:asyncpg" => python_asyncpg(),
+            "python:psycopg" => python_psycopg(),
             _ => return Err(Error::NotSupportedLanguage(lang)),
         };
-        Ok(resolver)
+        let naming = Naming::new(&request)?;
+        if let Some(case) = naming.class_case {
+            type_map.new_type_case = case.template().into();
+        }
+        Ok(TypeBuilder {
+            type_overrides,
+            enums,
+            catalog: request.catalog.clone(),
+            type_map,
+            naming,
+        })
+    }
+
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn naming(&self) -> &Naming {
+        &self.naming
     }
 
     pub fn declared(&self, name: &str) -> Type {
@@ -76,19 +107,34 @@ impl TypeBuilder {
     }
 
     pub fn composite(&self, type_schema: &Rc<str>, type_name: &Rc<str>) -> Type {
-        let ctx = &context! {type_schema => type_schema, type_name => type_name};
+        let class_name = self.class_name(type_name);
+        let ctx = context! {type_schema, type_name, class_name};
+        Type {
+            pgtype_name: Some(type_name.clone()),
+            pgtype_schema: Some(type_schema.clone()),
+            ..self.render_composite(ctx)
+        }
+    }
+
+    /// A model of the `models.rows` module, shared by the queries returning the same columns.
+    pub fn row_model(&self, declaration: &str) -> Type {
+        let ctx = context! {type_schema => "rows", class_name => declaration};
+        self.render_composite(ctx)
+    }
+
+    fn render_composite(&self, ctx: Value) -> Type {
         let composite = &self.type_map.composite;
         Type {
-            declaration: render(&composite.declaration, ctx).into(),
-            annotation: render(&composite.annotation, ctx).into(),
-            constructor: render(&composite.constructor, ctx).into(),
+            declaration: render(&composite.declaration, &ctx).into(),
+            annotation: render(&composite.annotation, &ctx).into(),
+            constructor: render(&composite.constructor, &ctx).into(),
             import: composite
                 .import
                 .iter()
-                .map(|import| render(import, ctx).into())
+                .map(|import| render(import, &ctx).into())
                 .collect(),
-            pgtype_name: Some(type_name.clone()),
-            pgtype_schema: Some(type_schema.clone()),
+            pgtype_name: None,
+            pgtype_schema: None,
         }
     }
 
@@ -97,7 +143,7 @@ impl TypeBuilder {
 
         type_ = self.array(type_, column.type_field.array_dimensions);
 
-        if let Some(enum_type) = self.try_enum(&column) {
+        if let Some(enum_type) = self.try_enum(column) {
             type_ = enum_type;
         }
 
@@ -105,7 +151,88 @@ impl TypeBuilder {
             return self.null(&type_);
         }
 
-        return type_;
+        type_
+    }
+
+    /// A placeholder value for `column`, which is its default when it is a constant
+    /// of a type that declares a `literal`.
+    pub fn sample(&self, column: &Column) -> Rc<str> {
+        let samples = &self.type_map.sample;
+        let ty = &column.type_field;
+        let enum_type = self.try_enum(column);
+        let default = column
+            .default
+            .as_deref()
+            .filter(|_| ty.array_dimensions == 0)
+            .and_then(constant);
+
+        if let (Some(enum_type), Some(value)) = (&enum_type, &default) {
+            return render(&samples.enum_, context!(type => enum_type, value => value)).into();
+        }
+
+        if let (None, Some(value)) = (&enum_type, &default) {
+            let config = self.resolve_type_config(&ty.schema_name, &ty.name);
+            if let Some(literal) = config.literal {
+                return render(&literal, context!(value => value)).into();
+            }
+        }
+
+        if column.is_nullable {
+            return render(&samples.null, context!()).into();
+        }
+
+        if ty.array_dimensions > 0 || ty.name.starts_with('_') {
+            return render(&samples.array, context!()).into();
+        }
+
+        if let Some(enum_type) = enum_type {
+            let value = self
+                .catalog
+                .schemas
+                .iter()
+                .filter(|schema| Some(&schema.name) == column.foreign_table_schema.as_ref())
+                .flat_map(|schema| schema.enums.iter())
+                .find(|enum_| Some(&enum_.name) == column.foreign_table_name.as_ref())
+                .and_then(|enum_| enum_.values.first().cloned());
+            if let Some(value) = value {
+                return render(&samples.enum_, context!(type => enum_type, value => value)).into();
+            }
+        }
+
+        if self
+            .resolve_from_catalog(&ty.schema_name, &ty.name)
+            .is_some()
+        {
+            let class_name = self.class_name(&ty.name);
+            let cx = context!(type_schema => ty.schema_name, type_name => ty.name, class_name);
+            return render(&samples.composite, cx).into();
+        }
+
+        let config = self.resolve_type_config(&ty.schema_name, &ty.name);
+        config
+            .sample
+            .or(self.type_map.wildcard.sample.clone())
+            .unwrap_or_default()
+    }
+
+    /// The value of `column` read from a row created by the factory of its parent model.
+    pub fn foreign_key_sample(
+        &self,
+        parent_schema: &Rc<str>,
+        parent: &Rc<str>,
+        column: &str,
+    ) -> Rc<str> {
+        let samples = &self.type_map.sample;
+        let class_name = self.class_name(parent);
+        let factory = render(
+            &samples.composite,
+            context!(type_schema => parent_schema, type_name => parent, class_name),
+        );
+        render(
+            &samples.foreign_key,
+            context!(factory => factory, column => column),
+        )
+        .into()
     }
 
     pub fn null(&self, type_: &Type) -> Type {
@@ -129,7 +256,7 @@ impl TypeBuilder {
         if self.enums.contains(&full_name) {
             return Some(self.composite(&schema_name, &type_name));
         }
-        return None;
+        None
     }
 
     pub fn array(&self, mut type_: Type, dim: i64) -> Type {
@@ -150,11 +277,63 @@ impl TypeBuilder {
             };
         }
 
-        return Type {
+        Type {
             pgtype_name: type_.pgtype_name.map(|ty| format!("_{}", ty).into()),
             pgtype_schema: type_.pgtype_schema.map(|ty| format!("_{}", ty).into()),
             ..type_
-        };
+        }
+    }
+
+    pub fn tuple(&self, types: &[Type]) -> Type {
+        let map = &self.type_map;
+        let cx = context!(types => types);
+        Type {
+            annotation: render(&map.tuple.annotation, &cx).into(),
+            declaration: render(&map.tuple.declaration, &cx).into(),
+            constructor: render(&map.tuple.constructor, &cx).into(),
+            import: types
+                .iter()
+                .flat_map(|type_| type_.import.iter().cloned())
+                .collect(),
+            pgtype_name: None,
+            pgtype_schema: None,
+        }
+    }
+
+    pub fn iterable(&self, type_: Type) -> Type {
+        let map = &self.type_map;
+        let cx = context!(type => type_);
+        Type {
+            annotation: render(&map.iterable.annotation, &cx).into(),
+            declaration: render(&map.iterable.declaration, &cx).into(),
+            constructor: render(&map.iterable.constructor, &cx).into(),
+            import: type_
+                .import
+                .iter()
+                .chain(map.iterable.import.iter())
+                .cloned()
+                .collect(),
+            pgtype_name: None,
+            pgtype_schema: None,
+        }
+    }
+
+    pub fn mapping(&self, key: Type, value: Type) -> Type {
+        let map = &self.type_map;
+        let cx = context!(key => key, value => value);
+        Type {
+            annotation: render(&map.mapping.annotation, &cx).into(),
+            declaration: render(&map.mapping.declaration, &cx).into(),
+            constructor: render(&map.mapping.constructor, &cx).into(),
+            import: key
+                .import
+                .iter()
+                .chain(value.import.iter())
+                .cloned()
+                .collect(),
+            pgtype_name: None,
+            pgtype_schema: None,
+        }
     }
 
     pub fn from_column_type(&self, ty: &ColumnType) -> Type {
@@ -162,7 +341,7 @@ impl TypeBuilder {
     }
 
     pub fn from_output_type(&self, ty: &OutputType) -> Type {
-        return self.resolve(&ty.schema, &ty.name);
+        self.resolve(&ty.schema, &ty.name)
     }
 
     pub fn resolve(&self, type_schema: &Rc<str>, type_name: &Rc<str>) -> Type {
@@ -175,7 +354,7 @@ impl TypeBuilder {
         }
 
         let type_name: Rc<str> = type_name.strip_prefix('_').unwrap().into();
-        return self.array(self.resolve_non_array(&type_schema, &type_name), 1);
+        self.array(self.resolve_non_array(type_schema, &type_name), 1)
     }
 
     pub fn resolve_non_array(&self, type_schema: &Rc<str>, type_name: &Rc<str>) -> Type {
@@ -183,7 +362,7 @@ impl TypeBuilder {
             return ty;
         };
 
-        let ty = self.resolve_type_config(&type_schema, &type_name);
+        let ty = self.resolve_type_config(type_schema, type_name);
 
         Type {
             declaration: Default::default(),
@@ -205,6 +384,12 @@ impl TypeBuilder {
         self.default_type_resolution(type_schema, type_name)
     }
 
+    /// The name of the class of a table or type, after the renames of the `naming` option.
+    fn class_name(&self, type_name: &Rc<str>) -> String {
+        let name = self.naming.table(type_name);
+        render(&self.type_map.new_type_case, context!(name))
+    }
+
     fn resolve_from_catalog(&self, type_schema: &Rc<str>, type_name: &Rc<str>) -> Option<Type> {
         let schema = self
             .catalog
@@ -217,26 +402,40 @@ impl TypeBuilder {
             .iter()
             .find(|model| &model.name == type_name)?;
 
-        Some(Self::composite(&self, type_schema, type_name))
+        Some(Self::composite(self, type_schema, type_name))
     }
 
     fn default_type_resolution(&self, type_schema: &str, type_name: &str) -> TypeConfig {
         self.type_map
             .schema
-            .get(&*type_schema)
-            .map(|schema| schema.get(&*type_name))
-            .flatten()
+            .get(type_schema)
+            .and_then(|schema| schema.get(&*type_name))
             .cloned()
             .unwrap_or_else(|| self.type_map.wildcard.clone())
     }
 }
 
-const PYTHON_ASYNCPG: LazyLock<TypeMap> = LazyLock::new(|| {
+/// The value of a column default that is a constant, such as `0`, `true` or `'draft'::text`.
+fn constant(default: &str) -> Option<String> {
+    static CONSTANT: OnceLock<Regex> = OnceLock::new();
+    let constant = CONSTANT.get_or_init(|| {
+        Regex::new(r#"^(?:'((?:[^']|'')*)'|(-?\d+(?:\.\d+)?|true|false))(?:::[\w ."]+)?$"#).unwrap()
+    });
+
+    let captures = constant.captures(default.trim())?;
+    match (captures.get(1), captures.get(2)) {
+        (Some(quoted), _) => Some(quoted.as_str().replace("''", "'")),
+        (_, Some(bare)) => Some(bare.as_str().to_string()),
+        _ => None,
+    }
+}
+
+fn python_asyncpg() -> TypeMap {
     let json = include_str!("../templates/python:asyncpg/types.json");
-    return serde_json::from_str(json).expect("failed to deserialize python:asyncpg/types.json ");
-});
+    serde_json::from_str(json).expect("failed to deserialize python:asyncpg/types.json ")
+}
 
-const PYTHON_PSYCOPG: LazyLock<TypeMap> = LazyLock::new(|| {
+fn python_psycopg() -> TypeMap {
     let json = include_str!("../templates/python:psycopg/types.json");
-    return serde_json::from_str(json).expect("failed to deserialize python:psycopg/types.json ");
-});
+    serde_json::from_str(json).expect("failed to deserialize python:psycopg/types.json ")
+}
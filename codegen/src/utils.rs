@@ -1,11 +1,7 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    fmt::format,
-    sync::{LazyLock, Mutex},
-};
+use std::{collections::BTreeMap, sync::Mutex};
 
 use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
-use minijinja::{Environment, Template};
+use minijinja::Environment;
 use regex::bytes::Regex;
 use serde::Serialize;
 
@@ -21,6 +17,7 @@ pub fn env() -> Environment<'static> {
     env.add_filter("starts_with", starts_with);
     env.add_filter("strip_prefix", strip_prefix);
     env.add_filter("regex_replace", regex_replace);
+    env.add_filter("escape_docstring", escape_docstring);
     env
 }
 
@@ -45,7 +42,17 @@ pub fn to_c_string(s: &str) -> String {
     format!("{:?}", s)
 }
 
-pub fn strip_prefix<'a>(text: &'a str, pattern: &str) -> String {
+/// Escapes text for a triple quoted docstring. Every quote is escaped, so the
+/// text can neither close the docstring nor end next to its closing quotes.
+pub fn escape_docstring(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+pub fn strip_prefix(text: &str, pattern: &str) -> String {
     text.strip_prefix(pattern).unwrap_or(text).to_string()
 }
 
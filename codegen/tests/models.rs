@@ -1,15 +1,779 @@
-use pgc_codegen::{model_modules::ModelModules, request::Request};
+use pgc_codegen::file_generator::FileGenerator;
+use pgc_codegen::request::{Query, Request};
 
 #[test]
 fn load_models() {
     // let model_modules = ModelModules::new(request);
-    let file_generator = pgc_codegen::file_generator::FileGenerator::new(&request()).unwrap();
+    let file_generator = FileGenerator::new(&request()).unwrap();
     let x = file_generator.render_files().unwrap();
     dbg!(x);
 }
 
+#[test]
+fn foreign_key_accessors() {
+    let mut request = request();
+    request.config.codegen.options =
+        serde_json::json!({"package": "app", "foreign_key_accessors": true});
+
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("async def author_of(self, book: AuthorOfBook)"));
+    assert!(book.contains("async def author_of_many(self, author_ids: list[uuid.UUID]) -> dict[uuid.UUID, models.public.Author]:"));
+    assert!(book.contains("return {row[0].id: row[0] for row in rows}"));
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains("async def books_of(self, author: BooksOfAuthor)"));
+    assert!(author.contains("where \"author_id\" = any($1)"));
+    assert!(author.contains("async def books_of_many(self, author_ids: list[uuid.UUID]) -> dict[uuid.UUID, list[models.public.Book]]:"));
+    assert!(author.contains(
+        "grouped: dict[uuid.UUID, list[models.public.Book]] = {key: [] for key in author_ids}"
+    ));
+    assert!(author.contains("grouped[row[0].author_id].append(row[0])"));
+}
+
+#[test]
+fn foreign_key_accessor_names() {
+    let mut request = request();
+    request.config.codegen.options =
+        serde_json::json!({"package": "app", "foreign_key_accessors": true});
+    let mut schemas = request.catalog.schemas.to_vec();
+    let mut models = schemas[0].models.to_vec();
+    let author_id = models[1].columns[2].clone();
+    for name in ["category", "status", "box"] {
+        let mut model = models[2].clone();
+        model.name = name.into();
+        model.columns = [model.columns[0].clone(), author_id.clone()].into();
+        models.push(model);
+    }
+    schemas[0].models = models.into();
+    request.catalog.schemas = schemas.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains("async def categories_of(self, author: CategoriesOfAuthor)"));
+    assert!(author.contains("async def status_of(self, author: StatusOfAuthor)"));
+    assert!(author.contains("async def boxes_of(self, author: BoxesOfAuthor)"));
+}
+
+#[test]
+fn unique_lookups() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({"package": "app", "unique_lookups": true});
+
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("async def get_by_isbn(self, isbn: str)"));
+    assert!(book.contains("async def get_many_by_isbn(self, isbns: list[str])"));
+
+    let mut queries = request.queries.to_vec();
+    query(&mut queries, "author.sql", "fetch_by_id").name = "get_by_id".into();
+    request.queries = queries.into();
+
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected the hand-written get_by_id to collide with the generated one");
+    };
+    assert!(error.to_string().contains(
+        r#""author.sql:2" the query "get_by_id" is generated as the method "get_by_id", which is also generated by the unique_lookups option"#
+    ));
+}
+
+#[test]
+fn composite_primary_keys() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({"package": "app", "unique_lookups": true});
+    let mut schemas = request.catalog.schemas.to_vec();
+    let mut models = schemas[0].models.to_vec();
+    let mut columns = models[1].columns.to_vec();
+    columns[1].is_primary_key = true;
+    models[1].columns = columns.into();
+    schemas[0].models = models.into();
+    request.catalog.schemas = schemas.into();
+
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("async def get_by_isbn(self, isbn: str)"));
+    assert!(!book.contains("async def get_by_id("));
+    assert!(!book.contains("async def get_by_title("));
+}
+
+#[test]
+fn paginate() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "paginate",
+        "title, id",
+        3,
+    );
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def fetch_books(self, id: uuid.UUID, page_size: int, cursor: str | None = None) -> FetchBooksPage:"
+    ));
+    assert!(author.contains(r#"order by (page."book")."title", (page."book")."id""#));
+    assert!(author.contains("next_cursor = _encode_cursor([last.title, last.id])"));
+}
+
+#[test]
+fn sort() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "sort",
+        "title, id",
+        3,
+    );
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def fetch_books(self, id: uuid.UUID, sort_by: FetchBooksSortBy = FetchBooksSortBy.TITLE, descending: bool = False) -> list[models.public.Book]:"
+    ));
+    assert!(author.contains(r#"order by (sorted."book")."id" desc"#));
+    assert!(author.contains("FETCH_BOOKS[sort_by.value, descending], id"));
+}
+
+#[test]
+fn optional_filters() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "optional",
+        "id",
+        3,
+    );
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def fetch_books(self, id: uuid.UUID | None) -> list[models.public.Book]:"
+    ));
+    assert!(author.contains(r#"where ($1::"pg_catalog"."uuid" is null or author.id = $1);"#));
+
+    let queries = request.queries.clone();
+    for (filter, expected) in [
+        (
+            "author.id = $1::uuid",
+            r#"where ($1::"pg_catalog"."uuid" is null or author.id = $1::uuid);"#,
+        ),
+        (
+            "$1 = author.id",
+            r#"where ($1::"pg_catalog"."uuid" is null or $1 = author.id);"#,
+        ),
+        (
+            "$1 = any(author.ids)",
+            r#"where ($1::"pg_catalog"."uuid" is null or $1 = any(author.ids));"#,
+        ),
+    ] {
+        let mut queries = queries.to_vec();
+        let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+        fetch_books.query = fetch_books.query.replace("author.id = $1", filter).into();
+        request.queries = queries.into();
+
+        let author = render(&request, "./author.py");
+        assert!(author.contains(expected), "{}", filter);
+    }
+
+    let mut queries = queries.to_vec();
+    let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+    fetch_books.query = fetch_books
+        .query
+        .replace("author.id = $1", "$1 = lower(author.id)")
+        .into();
+    request.queries = queries.into();
+    assert!(FileGenerator::new(&request).is_err());
+}
+
+#[test]
+fn transaction() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "transaction",
+        "Serializable",
+        3,
+    );
+    request.queries = queries.into();
+
+    let root = render(&request, "./__init__.py");
+    assert!(root.contains(
+        "async def transaction(self, isolation: Isolation | None = None, readonly: bool = False)"
+    ));
+    assert!(root.contains(
+        r#"Isolation = typing.Literal["serializable", "repeatable_read", "read_committed"]"#
+    ));
+    let author = render(&request, "./author.py");
+    assert!(author
+        .contains(r#"await self._require_transaction(connection, "fetch_books", "serializable")"#));
+    assert!(author.contains(r#"await connection.fetchval("show transaction_isolation")"#));
+
+    request.config.codegen.target = "python:psycopg".into();
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "        with self._connection() as connection:\n            self._require_transaction(connection, \"fetch_books\", \"serializable\")\n"
+    ));
+    assert!(author.contains("if connection.autocommit and connection.info.transaction_status"));
+}
+
+#[test]
+fn batch() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    query(&mut queries, "author.sql", "fetch_books").command = "batch".into();
+    request.queries = queries.clone().into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def fetch_books(self, params: list[uuid.UUID]) -> list[models.public.Book]:"
+    ));
+    assert!(author.contains("                for id in params\n"));
+
+    query(&mut queries, "author.sql", "count").command = "batch".into();
+    request.queries = queries.into();
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected a :batch query without parameters to be rejected");
+    };
+    assert!(error
+        .to_string()
+        .contains(r#"query "count" at "author.sql" cannot be used with :batch"#));
+}
+
+#[test]
+fn copy_from() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let upsert = query(&mut queries, "book.sql", "upsert");
+    upsert.command = "copyfrom".into();
+    request.queries = queries.clone().into();
+
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected an upsert to be rejected by :copyfrom");
+    };
+    assert!(error
+        .to_string()
+        .contains(r#"query "upsert" at "book.sql" cannot be used with :copyfrom"#));
+
+    let upsert = query(&mut queries, "book.sql", "upsert");
+    upsert.query = "insert into public.book (title, author_id) values ($1, $2)".into();
+    upsert.parameters = upsert.parameters[..2].into();
+    request.queries = queries.into();
+
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("async def upsert(self, rows: typing.Iterable[UpsertBook]) -> int:"));
+    assert!(book.contains(
+        "                columns=[\"title\", \"author_id\"],\n                schema_name=\"public\",\n"
+    ));
+
+    request.config.codegen.target = "python:psycopg".into();
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains(
+        r#"with cursor.copy("copy \"public\".\"book\" (\"title\", \"author_id\") from stdin") as copy:"#
+    ));
+    assert!(book.contains("copy.write_row((book.title, book.author_id, ))"));
+}
+
+#[test]
+fn iter() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+    fetch_books.command = "iter".into();
+    annotate(fetch_books, "prefetch", "500", 3);
+    request.queries = queries.clone().into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def fetch_books(self, id: uuid.UUID) -> typing.AsyncIterator[models.public.Book]:"
+    ));
+    assert!(author.contains("prefetch=500"));
+
+    request.config.codegen.target = "python:psycopg".into();
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "    def fetch_books(self, id: uuid.UUID) -> typing.Iterator[models.public.Book]:"
+    ));
+    assert!(author.contains(
+        r#"with connection.transaction(), connection.cursor(name="fetch_books") as cursor:"#
+    ));
+    assert!(author.contains("cursor.itersize = 500"));
+
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "prefetch",
+        "0",
+        3,
+    );
+    request.queries = queries.into();
+    assert!(FileGenerator::new(&request).is_err());
+}
+
+#[test]
+fn execrows() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    query(&mut queries, "author.sql", "fetch_books").command = "execrows".into();
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains("async def fetch_books(self, id: uuid.UUID) -> int:"));
+    assert!(
+        author.contains("status = await connection.execute(\n                FETCH_BOOKS, id\n")
+    );
+    assert!(author.contains("return int(status.split()[-1])"));
+
+    request.config.codegen.target = "python:psycopg".into();
+    let author = render(&request, "./author.py");
+    assert!(author.contains("def fetch_books(self, id: uuid.UUID) -> int:"));
+    assert!(author.contains("FETCH_BOOKS, {\"p1\": id}\n            ).rowcount"));
+}
+
+#[test]
+fn pool() {
+    let request = request();
+
+    let root = render(&request, "./__init__.py");
+    assert!(root
+        .contains("return await asyncpg.create_pool(*args, init=init_pool_connection, **kwargs)"));
+    let author = render(&request, "./author.py");
+    assert!(author.contains("async with self.connection.acquire() as connection:"));
+    assert!(author.contains("rows = await connection.fetch("));
+}
+
+#[test]
+fn instrumentation() {
+    let mut request = request();
+    request.config.codegen.options =
+        serde_json::json!({"package": "app", "instrumentation": "opentelemetry"});
+
+    let root = render(&request, "./__init__.py");
+    assert!(root.contains("with _tracer.start_as_current_span("));
+    let book_author = render(&request, "./book/author.py");
+    assert!(book_author.contains(
+        r#"async with self._connection() as connection, app.instrument("fetch_by_author_id", "book.author", FETCH_BY_AUTHOR_ID):"#
+    ));
+
+    request.config.codegen.options =
+        serde_json::json!({"package": "app", "instrumentation": "otel"});
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected the unknown instrumentation to be rejected");
+    };
+    assert!(error
+        .to_string()
+        .starts_with(r#"invalid codegen option "instrumentation": unknown variant `otel`"#));
+}
+
+#[test]
+fn protocols() {
+    let book = render(&request(), "./book/__init__.py");
+    assert!(book.contains(
+        "class BookQueriesProtocol(typing.Protocol):\n    author: author.AuthorQueriesProtocol\n"
+    ));
+    assert!(book.contains(
+        "    async def fetch_by_id(self, id: uuid.UUID) -> models.public.Book | None: ...\n"
+    ));
+    assert!(book.contains("class BookQueries(BookQueriesProtocol):"));
+}
+
+#[test]
+fn fake_queries() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({"package": "app", "fake_queries": true});
+
+    let root = render(&request, "./__init__.py");
+    assert!(root.contains("self.book = book.FakeBookQueries()"));
+    let author = render(&request, "./author.py");
+    assert!(author.contains("class FakeAuthorQueries(AuthorQueriesProtocol):"));
+    assert!(
+        author.contains("def expect_fetch_books(self, returns: list[models.public.Book]) -> None:")
+    );
+    assert!(author.contains(r#"return self._call("fetch_books", {"id": id}, [])"#));
+}
+
+#[test]
+fn model_factories() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({"package": "app", "model_factories": true});
+
+    let models = render(&request, "models/public.py");
+    assert!(models.contains("def make_book(**overrides: typing.Any) -> Book:"));
+    assert!(models.contains(r#""is_best_seller": False,"#));
+    assert!(models.contains(r#"values["author_id"] = models.public.make_author().id"#));
+
+    let mut schemas = request.catalog.schemas.to_vec();
+    let mut models = schemas[0].models.to_vec();
+    let mut columns = models[0].columns.to_vec();
+    columns[1].is_foreign_key = true;
+    columns[1].foreign_table_schema = Some("public".into());
+    columns[1].foreign_table_name = Some("book".into());
+    models[0].columns = columns.into();
+    schemas[0].models = models.into();
+    request.catalog.schemas = schemas.into();
+
+    let models = render(&request, "models/public.py");
+    assert!(!models.contains("models.public.make_author()"));
+    assert!(!models.contains("models.public.make_book()"));
+}
+
+#[test]
+fn docstrings() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "count"),
+        "doc",
+        "Counts the authors.",
+        14,
+    );
+    query(&mut queries, "author.sql", "fetch_all").comment =
+        Some("Lists every author.\nIn no particular order.".into());
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains(
+        "async def count(self) -> int | None:\n        \"\"\"Counts the authors.\"\"\"\n"
+    ));
+    assert!(author.contains(
+        "        \"\"\"Lists every author.\n        In no particular order.\n        \"\"\"\n"
+    ));
+
+    let mut queries = request.queries.to_vec();
+    query(&mut queries, "author.sql", "fetch_all").comment =
+        Some(r#"Lists the authors named "Anonymous""#.into());
+    request.queries = queries.into();
+    let author = render(&request, "./author.py");
+    assert!(author.contains(r#""""Lists the authors named \"Anonymous\"""""#));
+}
+
+#[test]
+fn schema_comments() {
+    let mut request = request();
+    let mut schemas = request.catalog.schemas.to_vec();
+    let mut models = schemas[0].models.to_vec();
+    let mut columns = models[1].columns.to_vec();
+    columns[4].comment = Some("The ISBN-13 of the first edition.".into());
+    models[1].columns = columns.into();
+    models[1].comment = Some("Every book in the catalog.".into());
+    schemas[0].models = models.into();
+    request.catalog.schemas = schemas.into();
+
+    let models = render(&request, "models/public.py");
+    assert!(models.contains("class Book:\n    \"\"\"Every book in the catalog.\"\"\"\n"));
+    assert!(models.contains("    isbn: str\n    \"\"\"The ISBN-13 of the first edition.\"\"\"\n"));
+
+    let mut schemas = request.catalog.schemas.to_vec();
+    let mut models = schemas[0].models.to_vec();
+    models[1].comment = Some(r#"Every book, or "title""#.into());
+    schemas[0].models = models.into();
+    request.catalog.schemas = schemas.into();
+    let models = render(&request, "models/public.py");
+    assert!(models.contains(r#"    """Every book, or \"title\"""""#));
+}
+
+#[test]
+fn deprecated() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "deprecated",
+        "use fetch_all instead",
+        7,
+    );
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author.contains("import typing_extensions"));
+    assert!(author.contains(
+        "    @typing_extensions.deprecated(\"use fetch_all instead\")\n    async def fetch_books("
+    ));
+
+    let diagnostics = FileGenerator::new(&request).unwrap().diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 7);
+    assert_eq!(
+        diagnostics[0].message,
+        "query \"fetch_books\" is deprecated: use fetch_all instead"
+    );
+}
+
+#[test]
+fn reserved_words() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+    let mut parameters = fetch_books.parameters.to_vec();
+    parameters[0].name = "from".into();
+    fetch_books.parameters = parameters.into();
+    fetch_books.name = "import".into();
+    let fetch_by_author_id = query(&mut queries, "book.sql", "fetch_by_author_id");
+    let mut output = fetch_by_author_id.output.to_vec();
+    output[0].name = "class".into();
+    fetch_by_author_id.output = output.into();
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(
+        author.contains("async def import_(self, from_: uuid.UUID) -> list[models.public.Book]:")
+    );
+    assert!(author.contains("IMPORT, from_\n"));
+    let book_author = render(&request, "./book/author.py");
+    assert!(book_author.contains("    class_: models.public.Author\n"));
+    assert!(book_author.contains(r#"FetchByAuthorIdRow(book=row["book"], class_=row["class"])"#));
+}
+
+#[test]
+fn naming() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({
+        "package": "app",
+        "naming": {
+            "field_case": "camel",
+            "method_case": "camel",
+            "row_suffix": "result",
+            "tables": {"author": "writer"},
+            "columns": {"name": "full_name"}
+        }
+    });
+
+    let models = render(&request, "models/public.py");
+    assert!(models.contains("class Writer:\n    id: uuid.UUID\n    fullName: str\n"));
+    assert!(models.contains("    authorId: uuid.UUID\n"));
+    let author = render(&request, "./author.py");
+    assert!(
+        author.contains("async def fetchById(self, id: uuid.UUID) -> models.public.Writer | None:")
+    );
+    assert!(author.contains(
+        r#"FetchAllResult(birthday=row["birthday"], id=row["id"], fullName=row["name"])"#
+    ));
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("book.title, book.authorId, book.year"));
+
+    request.config.codegen.options["fake_queries"] = true.into();
+    let author = render(&request, "./author.py");
+    assert!(author
+        .contains("def expect_fetchById(self, returns: models.public.Writer | None) -> None:"));
+    assert!(author.contains(r#"return self._call("fetchById", {"id": id}, None)"#));
+
+    let mut queries = request.queries.to_vec();
+    let mut duplicate = query(&mut queries, "author.sql", "fetch_all").clone();
+    duplicate.name = "fetchAll".into();
+    queries.push(duplicate);
+    request.queries = queries.into();
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected fetch_all and fetchAll to collide");
+    };
+    assert!(error.to_string().contains(
+        r#"the query "fetchAll" is generated as the method "fetchAll" of the query "fetch_all""#
+    ));
+
+    request.config.codegen.options["naming"] = serde_json::json!({"method_case": "kebab"});
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected the unknown case to be rejected");
+    };
+    assert!(error
+        .to_string()
+        .starts_with(r#"invalid codegen option "naming": unknown variant `kebab`"#));
+}
+
+#[test]
+fn named_models() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let fetch_all = query(&mut queries, "author.sql", "fetch_all");
+    annotate(fetch_all, "returns", "AuthorSummary", 4);
+    let mut newest = fetch_all.clone();
+    newest.name = "fetch_newest".into();
+    annotate(
+        query(&mut queries, "book.sql", "upsert"),
+        "input",
+        "book: BookInput",
+        11,
+    );
+    queries.push(newest.clone());
+    request.queries = queries.clone().into();
+
+    let author = render(&request, "./author.py");
+    assert_eq!(author.matches("class AuthorSummary:").count(), 1);
+    assert!(author.contains("async def fetch_newest(self) -> list[AuthorSummary]:"));
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("class BookInput(typing.Protocol):"));
+    assert!(book.contains("async def upsert(self, book: BookInput) -> models.public.Book | None:"));
+
+    let mut output = newest.output.to_vec();
+    output.pop();
+    newest.output = output.into();
+    newest.name = "fetch_names".into();
+    queries.push(newest);
+    request.queries = queries.into();
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected the different fields of AuthorSummary to be reported");
+    };
+    assert!(error.to_string().contains(
+        r#"the model "AuthorSummary" of the query "fetch_names" has different fields than the one of the query "fetch_all""#
+    ));
+}
+
+#[test]
+fn deduplicate_rows() {
+    let mut request = request();
+    request.config.codegen.options =
+        serde_json::json!({"package": "app", "deduplicate_rows": true});
+    let mut queries = request.queries.to_vec();
+    let mut list_authors = query(&mut queries, "author.sql", "fetch_all").clone();
+    list_authors.name = "list_authors".into();
+    list_authors.path = "book.sql".into();
+    queries.push(list_authors);
+    request.queries = queries.into();
+
+    let rows = render(&request, "models/rows.py");
+    assert!(rows.contains("class FetchAllRow:\n    id: uuid.UUID\n"));
+    let author = render(&request, "./author.py");
+    assert!(!author.contains("class FetchAllRow"));
+    assert!(author.contains("async def fetch_all(self) -> list[models.rows.FetchAllRow]:"));
+    let book = render(&request, "./book/__init__.py");
+    assert!(book.contains("return [models.rows.FetchAllRow(**row) for row in rows]"));
+
+    let mut queries = request.queries.to_vec();
+    query(&mut queries, "book.sql", "list_authors").name = "all_authors".into();
+    for _ in 0..2 {
+        queries.reverse();
+        request.queries = queries.clone().into();
+        let rows = render(&request, "models/rows.py");
+        assert!(rows.contains("class AllAuthorsRow:\n    id: uuid.UUID\n"));
+        assert!(!rows.contains("class FetchAllRow"));
+    }
+}
+
+#[test]
+fn single_file_layout() {
+    let mut request = request();
+    request.config.codegen.options = serde_json::json!({"package": "app", "layout": "single_file"});
+    let files = FileGenerator::new(&request)
+        .unwrap()
+        .render_files()
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "./__init__.py");
+
+    let module = render(&request, "./__init__.py");
+    assert!(module.contains(
+        "class models:\n    class public:\n        @dataclasses.dataclass\n        class Author:\n"
+    ));
+    assert!(module.contains("BOOK_AUTHOR_FETCH_BY_AUTHOR_ID = \"\"\""));
+    assert!(module.contains("self.author = BookAuthorQueries(connection)"));
+    assert!(!module.contains("from app import models"));
+}
+
+#[test]
+fn name_collisions() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let fetch_by_id = query(&mut queries, "book.sql", "fetch_by_id");
+    let mut duplicate = fetch_by_id.clone();
+    annotate(&mut duplicate, "name", "fetch_by_id :one", 40);
+    let mut shadowing = fetch_by_id.clone();
+    shadowing.name = "author".into();
+    let mut keyword = fetch_by_id.clone();
+    keyword.name = "import".into();
+    let mut escaped = fetch_by_id.clone();
+    escaped.name = "import_".into();
+    queries.push(duplicate);
+    queries.push(shadowing);
+    queries.push(keyword);
+    queries.push(escaped);
+    let fetch_by_author_id = query(&mut queries, "book.sql", "fetch_by_author_id");
+    let mut output = fetch_by_author_id.output.to_vec();
+    output[0].name = "class".into();
+    output[1].name = "class_".into();
+    fetch_by_author_id.output = output.into();
+    request.queries = queries.into();
+
+    let Err(error) = FileGenerator::new(&request) else {
+        panic!("expected the name collisions to be reported");
+    };
+    let error = error.to_string();
+    assert!(error.contains(
+        r#""book.sql:40" the query "fetch_by_id" is already defined in the namespace "book" at "book.sql:2""#
+    ));
+    assert!(error.contains(
+        r#""book.sql:2" the query "author" has the same name as the namespace "book.author""#
+    ));
+    assert!(error.contains(
+        r#""book.sql:2" the query "import_" is generated as the method "import_" of the query "import" at "book.sql:2""#
+    ));
+    assert!(error.contains(
+        r#""book.sql:5" the columns "class" and "class_" of the query "fetch_by_author_id" are both generated as the field "class_""#
+    ));
+}
+
+#[test]
+fn list_parameters() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+    let mut parameters = fetch_books.parameters.to_vec();
+    parameters[0].is_list = true;
+    fetch_books.parameters = parameters.into();
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author
+        .contains("async def fetch_books(self, id: list[uuid.UUID]) -> list[models.public.Book]:"));
+
+    let mut queries = request.queries.to_vec();
+    let fetch_books = query(&mut queries, "author.sql", "fetch_books");
+    let mut parameters = fetch_books.parameters.to_vec();
+    parameters[0].type_.name = "_uuid".into();
+    fetch_books.parameters = parameters.into();
+    request.queries = queries.into();
+
+    let author = render(&request, "./author.py");
+    assert!(author
+        .contains("async def fetch_books(self, id: list[uuid.UUID]) -> list[models.public.Book]:"));
+}
+
+#[test]
+fn paginate_unknown_column() {
+    let mut request = request();
+    let mut queries = request.queries.to_vec();
+    annotate(
+        query(&mut queries, "author.sql", "fetch_books"),
+        "paginate",
+        "email",
+        3,
+    );
+    request.queries = queries.into();
+
+    assert!(FileGenerator::new(&request).is_err());
+}
+
 fn request() -> Request {
     serde_json::from_str(REQUEST).unwrap()
 }
 
+/// Renders the request and returns the content of the file at `path`.
+fn render(request: &Request, path: &str) -> String {
+    let files = FileGenerator::new(request).unwrap().render_files().unwrap();
+    let Some(file) = files.iter().find(|file| file.path == path) else {
+        panic!("no file was rendered at {:?}", path);
+    };
+    file.content.clone()
+}
+
+fn query<'a>(queries: &'a mut [Query], path: &str, name: &str) -> &'a mut Query {
+    queries
+        .iter_mut()
+        .find(|query| &*query.path == path && &*query.name == name)
+        .unwrap()
+}
+
+fn annotate(query: &mut Query, name: &str, value: &str, line: i64) {
+    let annotation = pgc_codegen::request::Annotation {
+        value: Some(value.into()),
+        line,
+    };
+    std::rc::Rc::make_mut(&mut query.annotations).insert(name.into(), annotation);
+}
+
 const REQUEST: &str = r#"{"catalog":{"schemas":[{"name":"public","enums":[],"models":[{"kind":"table","name":"author","columns":[{"name":"id","type":{"name":"uuid","display":"uuid","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":"gen_random_uuid()","is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":true,"foreign_table_name":null,"foreign_table_schema":null},{"name":"name","type":{"name":"text","display":"text","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null},{"name":"birthday","type":{"name":"date","display":"date","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":true,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null}]},{"kind":"table","name":"book","columns":[{"name":"id","type":{"name":"uuid","display":"uuid","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":"gen_random_uuid()","is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":true,"foreign_table_name":null,"foreign_table_schema":null},{"name":"title","type":{"name":"text","display":"text","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null},{"name":"author_id","type":{"name":"uuid","display":"uuid","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":true,"is_primary_key":false,"foreign_table_name":"author","foreign_table_schema":"public"},{"name":"year","type":{"name":"int4","display":"integer","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null},{"name":"isbn","type":{"name":"text","display":"text","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":true,"is_nullable":false,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null},{"name":"is_best_seller","type":{"name":"bool","display":"boolean","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":"false","is_unique":false,"is_nullable":true,"is_foreign_key":false,"is_primary_key":false,"foreign_table_name":null,"foreign_table_schema":null},{"name":"genre","type":{"name":"text","display":"text","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":true,"is_primary_key":false,"foreign_table_name":"genre","foreign_table_schema":"public"}]},{"kind":"table","name":"genre","columns":[{"name":"id","type":{"name":"text","display":"text","is_array":false,"schema_name":"pg_catalog","is_composite":false,"array_dimensions":0},"default":null,"is_unique":false,"is_nullable":false,"is_foreign_key":false,"is_primary_key":true,"foreign_table_name":null,"foreign_table_schema":null}]}]}]},"queries":[{"query":"select book from book where id = $1;","name":"fetch_by_id","command":"one","path":"book.sql","annotations":{"name":{"value":"fetch_by_id :one","line":2}},"output":[{"name":"book","type":{"schema":"public","name":"book","id":16401}}],"parameters":[{"name":"id","not_null":true,"type":{"schema":"pg_catalog","name":"uuid","id":2950}}]},{"query":"select author, book from author\njoin book on book.author_id = author.id\nwhere author.id = $1;","name":"fetch_by_author_id","command":"many","path":"book.sql","annotations":{"name":{"value":"fetch_by_author_id :many","line":5},"namespace":{"value":"book.author","line":6}},"output":[{"name":"author","type":{"schema":"public","name":"author","id":16386}},{"name":"book","type":{"schema":"public","name":"book","id":16401}}],"parameters":[{"name":"id","not_null":true,"type":{"schema":"pg_catalog","name":"uuid","id":2950}}]},{"query":"insert into book (\n    title,\n    author_id,\n    year,\n    isbn,\n    genre,\n    is_best_seller\n)\nvalues (\n    $1,\n    $2,\n    $3,\n    $4,\n    $5,\n    coalesce($6, false)\n)\non conflict (id) do update set\n    title =          $1,\n    author_id =      $2,\n    year =           $3,\n    isbn =           $4,\n    is_best_seller = coalesce($6, false),\n    genre =          $5\nreturning book;","name":"upsert","command":"one","path":"book.sql","annotations":{"name":{"value":"upsert :one","line":12}},"output":[{"name":"book","type":{"schema":"public","name":"book","id":16401}}],"parameters":[{"name":"book.title","not_null":true,"type":{"schema":"pg_catalog","name":"text","id":25}},{"name":"book.author_id","not_null":true,"type":{"schema":"pg_catalog","name":"uuid","id":2950}},{"name":"book.year","not_null":true,"type":{"schema":"pg_catalog","name":"int4","id":23}},{"name":"book.isbn","not_null":true,"type":{"schema":"pg_catalog","name":"text","id":25}},{"name":"book.genre","not_null":true,"type":{"schema":"pg_catalog","name":"text","id":25}},{"name":"book.is_best_seller","not_null":false,"type":{"schema":"pg_catalog","name":"bool","id":16}}]},{"query":"select author from author where id = $1;","name":"fetch_by_id","command":"one","path":"author.sql","annotations":{"name":{"value":"fetch_by_id :one","line":2}},"output":[{"name":"author","type":{"schema":"public","name":"author","id":16386}}],"parameters":[{"name":"id","not_null":true,"type":{"schema":"pg_catalog","name":"uuid","id":2950}}]},{"query":"select * from author;","name":"fetch_all","command":"many","path":"author.sql","annotations":{"name":{"value":"fetch_all :many","line":5}},"output":[{"name":"id","type":{"schema":"pg_catalog","name":"uuid","id":2950}},{"name":"name","type":{"schema":"pg_catalog","name":"text","id":25}},{"name":"birthday","type":{"schema":"pg_catalog","name":"date","id":1082}}],"parameters":[]},{"query":"select book\nfrom author\njoin book on author.id = book.author_id\nwhere author.id = $1;","name":"fetch_books","command":"many","path":"author.sql","annotations":{"name":{"value":"fetch_books :many","line":8}},"output":[{"name":"book","type":{"schema":"public","name":"book","id":16401}}],"parameters":[{"name":"id","not_null":true,"type":{"schema":"pg_catalog","name":"uuid","id":2950}}]},{"query":"select count(*) from author;","name":"count","command":"val","path":"author.sql","annotations":{"name":{"value":"count :val","line":15}},"output":[{"name":"count","type":{"schema":"pg_catalog","name":"int8","id":20}}],"parameters":[]}],"config":{"version":"1","queries":["book.sql","author.sql","queries.sql"],"disable_cache":false,"database":{"migrations":"schema.sql","pglite":{"extensions":{"vector":"@electric-sql/pglite/vector"}}},"codegen":{"out":"./src/queries","target":"python:asyncpg","options":null},"env_file":[".env"]}}"#;
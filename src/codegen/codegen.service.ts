@@ -9,13 +9,17 @@ export class CodegenService {
   }
 
   async generate(payload: object) {
-    const { error, files } = await this.runWasmCodegenModule(
+    const { error, files, diagnostics } = await this.runWasmCodegenModule(
       payload,
     );
     if (!files) {
       throw Error(`${error}`);
     }
 
+    for (const { path, line, message } of diagnostics ?? []) {
+      console.warn(`warning: "${path}:${line}" ${message}.`);
+    }
+
     const outDir = path.join(this.configService.config.codegen?.out, "/");
 
     await this.clearDirectory(outDir);
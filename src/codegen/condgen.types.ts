@@ -3,10 +3,17 @@ interface OutputFile {
   path: string;
 }
 
+interface Diagnostic {
+  path: string;
+  line: number;
+  message: string;
+}
+
 export type CodegenResponse = {
   files: OutputFile[];
+  diagnostics?: Diagnostic[];
   error: undefined;
-} | { files: undefined; error: string };
+} | { files: undefined; diagnostics: undefined; error: string };
 
 interface WasmPlugin {
   instance: WebAssembly.Instance;
@@ -28,6 +28,8 @@ const PluginConfig = z.object({
 const TypeOverride = z.object({
   annotation: z.string(),
   import: z.string().array().optional(),
+  sample: z.string().optional(),
+  literal: z.string().optional(),
 });
 
 const EnumOptions = z.string().or(z.record(z.string(), z.string().array()));
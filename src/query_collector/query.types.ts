@@ -11,6 +11,7 @@ export interface Parameter {
   name: string;
   not_null: boolean;
   type: PGType;
+  is_list: boolean;
 }
 
 interface Column {
@@ -33,6 +34,7 @@ export interface Query {
   command: string;
   name: string;
   annotations: Record<string, Annotation>;
+  comment?: string;
   query: string;
   parameters: Parameter[];
   path: string;
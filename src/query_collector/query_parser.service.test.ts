@@ -0,0 +1,37 @@
+import { assertEquals } from "@std/assert";
+import type { PGService } from "../pg/pg.service.ts";
+import { QueryParserService } from "./query_parser.service.ts";
+
+const parser = new QueryParserService({} as PGService);
+
+Deno.test(function replaceListParameters() {
+  const { sql, describedSql, lists } = parser.replaceListParameters(
+    "select * from book where id in $ids[] and tags && $tags[]",
+  );
+
+  assertEquals(
+    sql,
+    "select * from book where id = any($ids) and tags && $tags",
+  );
+  assertEquals(
+    describedSql,
+    "select * from book where id = $ids and tags && $tags",
+  );
+  assertEquals(lists, new Set(["ids", "tags"]));
+});
+
+Deno.test(function replaceAnnotatedListParameters() {
+  const { sql, describedSql, lists } = parser.replaceListParameters(
+    "-- @param ids: list\nselect * from book where id in ($ids)",
+  );
+
+  assertEquals(
+    sql,
+    "-- @param ids: list\nselect * from book where id = any($ids)",
+  );
+  assertEquals(
+    describedSql,
+    "-- @param ids: list\nselect * from book where id = $ids",
+  );
+  assertEquals(lists, new Set(["ids"]));
+});
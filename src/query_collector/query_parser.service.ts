@@ -5,6 +5,8 @@ const ANNOTATION = /\s*--\s*@(\w+)(?::\s*([^\n]+))?\s*/;
 const PARAMETER =
   /([?\$][A-Za-z]\w*|\$\([A-Za-z]\w*\))|[?\$]\(([A-Za-z]\w*).([A-Za-z]\w*)\)/g;
 const COMMENT = /\s*--[^\n]+\n/g;
+const LIST_PARAMETER = /([?\$][A-Za-z]\w*)\[\]/g;
+const LIST_ANNOTATION = /--\s*@param\s+([A-Za-z]\w*)\s*:\s*list\b/g;
 
 export class QueryParserService {
   constructor(
@@ -16,10 +18,14 @@ export class QueryParserService {
 
     if (!annotations["name"]) return;
     const { name, command } = this.parseName(rawQuery, annotations)!;
-    const { query, params } = this.replaceParameters(rawQuery.sql);
+    const { sql, describedSql, lists } = this.replaceListParameters(
+      rawQuery.sql,
+    );
+    const { query, params } = this.replaceParameters(sql);
+    const described = this.replaceParameters(describedSql);
     const { inputs, outputs } = await this.pgService.describe({
       ...rawQuery,
-      sql: query,
+      sql: described.query,
     });
 
     return {
@@ -28,11 +34,13 @@ export class QueryParserService {
       command,
       path: rawQuery.file.path,
       annotations,
+      comment: this.parseComment(rawQuery),
       output: outputs,
       parameters: params.entries().map(([name, nullableness], i) => ({
         name: name,
         not_null: nullableness == Nullableness.NON_NULL,
         type: inputs[i],
+        is_list: lists.has(name),
       })).filter(({ type }) => type).toArray(),
     };
   }
@@ -52,14 +60,31 @@ export class QueryParserService {
     return annotations;
   }
 
+  // The comment block right above a query, without its annotations. Blank
+  // lines end a block, so file headers are not included.
+  parseComment(query: RawQuery): string | undefined {
+    let lines: string[] = [];
+    for (const line of query.sql.split("\n")) {
+      const trimmed = line.trim();
+      if (trimmed == "") {
+        lines = [];
+        continue;
+      }
+      if (!trimmed.startsWith("--")) break;
+      if (line.match(ANNOTATION)) continue;
+      lines.push(trimmed.replace(/^--\s?/, "").trimEnd());
+    }
+    return lines.length ? lines.join("\n") : undefined;
+  }
+
   parseName(query: RawQuery, annotations: Record<string, Annotation>) {
     const name = annotations["name"];
     const match = name.value.match(
-      /(\S+)\s+:(val|exec|one|many)/,
+      /(\S+)\s+:(val|execrows|exec|one|many|batch|copyfrom|iter)\b/,
     );
     if (!match) {
       throw Error(
-        `"${query.file.path}:${name.line}" invalid query return specifier (expected one of: :val, :one, :many, :exec)`,
+        `"${query.file.path}:${name.line}" invalid query return specifier (expected one of: :val, :one, :many, :exec, :execrows, :batch, :copyfrom, :iter)`,
       );
     }
 
@@ -71,6 +96,39 @@ export class QueryParserService {
     };
   }
 
+  // List parameters are described through their element type, so `in $ids[]`
+  // is sent to postgres as `= $n` and generated as `= any($n)`.
+  replaceListParameters(rawQuery: string) {
+    const lists = new Set<string>();
+
+    for (const [, name] of rawQuery.matchAll(LIST_ANNOTATION)) {
+      lists.add(name);
+    }
+    for (const [, param] of rawQuery.matchAll(LIST_PARAMETER)) {
+      lists.add(param.slice(1));
+    }
+
+    let sql = rawQuery.replaceAll(LIST_PARAMETER, "$1");
+    let describedSql = sql;
+
+    for (const name of lists) {
+      const membership = new RegExp(
+        `\\bin\\s*(?:\\(\\s*([?$]${name})\\s*\\)|([?$]${name})\\b)`,
+        "gi",
+      );
+      sql = sql.replaceAll(
+        membership,
+        (_, group, bare) => `= any(${group ?? bare})`,
+      );
+      describedSql = describedSql.replaceAll(
+        membership,
+        (_, group, bare) => `= ${group ?? bare}`,
+      );
+    }
+
+    return { sql, describedSql, lists };
+  }
+
   replaceParameters(rawQuery: string) {
     const params = new Map<string, Nullableness>();
 
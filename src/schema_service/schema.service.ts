@@ -62,17 +62,18 @@ const LOAD_SCHEMA_QUERY = `
           conrelid,
           unnest(conkey) AS attnum
       FROM pg_constraint
-      WHERE contype = 'u'
+      WHERE contype = 'u' AND array_length(conkey, 1) = 1
   ),
 
   fk_cols AS (
       SELECT
           conrelid,
-          unnest(conkey) AS attnum,
+          k.attnum,
           confrelid,
-          confkey[1] AS conf_attnum -- assuming single-column FK
-      FROM pg_constraint
-      WHERE contype = 'f'
+          k.conf_attnum
+      FROM pg_constraint,
+      unnest(conkey, confkey) AS k(attnum, conf_attnum)
+      WHERE contype = 'f' AND array_length(conkey, 1) = 1
   ),
 
   columns AS (
@@ -95,6 +96,12 @@ const LOAD_SCHEMA_QUERY = `
           (fk.conrelid IS NOT NULL) AS is_foreign_key,
           n2.nspname AS foreign_table_schema,
           c2.relname AS foreign_table_name,
+          fa.attname AS foreign_column_name,
+          col_description(c.oid, a.attnum) AS comment,
+          coalesce(
+              obj_description(c.oid, 'pg_class'),
+              obj_description(c.reltype, 'pg_type')
+          ) AS table_comment,
           c.relkind
       FROM pg_class c
       JOIN pg_namespace n ON n.oid = c.relnamespace
@@ -106,6 +113,7 @@ const LOAD_SCHEMA_QUERY = `
       LEFT JOIN fk_cols fk ON fk.conrelid = c.oid AND fk.attnum = a.attnum
       LEFT JOIN pg_class c2 ON c2.oid = fk.confrelid
       LEFT JOIN pg_namespace n2 ON n2.oid = c2.relnamespace
+      LEFT JOIN pg_attribute fa ON fa.attrelid = fk.confrelid AND fa.attnum = fk.conf_attnum
       JOIN pg_namespace tn ON tn.oid = t.typnamespace
       LEFT JOIN pg_type te ON te.oid = t.typelem
       LEFT JOIN pg_namespace ne ON ne.oid = te.typnamespace
@@ -134,6 +142,7 @@ const LOAD_SCHEMA_QUERY = `
                   SELECT jsonb_agg(
                       jsonb_build_object(
                           'name', table_name,
+                          'comment', table_comment,
                           'kind', case
                             when relkind = 'r' then 'table'
                             when relkind = 'c' then 'composite'
@@ -151,7 +160,9 @@ const LOAD_SCHEMA_QUERY = `
                                       'is_primary_key', is_primary_key,
                                       'is_foreign_key', is_foreign_key,
                                       'foreign_table_schema', foreign_table_schema,
-                                      'foreign_table_name', foreign_table_name
+                                      'foreign_table_name', foreign_table_name,
+                                      'foreign_column_name', foreign_column_name,
+                                      'comment', comment
                                   )
                               )
                               FROM columns c2
@@ -161,7 +172,7 @@ const LOAD_SCHEMA_QUERY = `
                       )
                   )
                   FROM (
-                      SELECT DISTINCT table_name, relkind
+                      SELECT DISTINCT table_name, relkind, table_comment
                       FROM columns
                       WHERE schema_name = schemas.schema_name
                   ) t
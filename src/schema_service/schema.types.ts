@@ -16,6 +16,7 @@ export interface Enum {
 export interface Table {
   kind: "table" | "view" | "materialized view" | "composite";
   name: string;
+  comment: string | null;
   columns: Column[];
 }
 
@@ -29,6 +30,8 @@ export interface Column {
   is_primary_key: boolean;
   foreign_table_name: string | null;
   foreign_table_schema: string | null;
+  foreign_column_name: string | null;
+  comment: string | null;
 }
 
 export interface SQLType {